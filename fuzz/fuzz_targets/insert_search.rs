@@ -0,0 +1,54 @@
+#![no_main]
+
+// pulls in the quadtree implementation directly since the crate ships as a
+// binary rather than a library; `main` from quadtree.rs is simply unused here
+#[path = "../../src/quadtree.rs"]
+#[allow(dead_code)]
+mod quadtree;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use quadtree::{contains, create_quad_tree, insert, search, validate, Boundary};
+
+#[derive(Arbitrary, Debug)]
+struct FuzzOp {
+    x: f64,
+    y: f64,
+}
+
+fuzz_target!(|ops: Vec<FuzzOp>| {
+    let boundary = Boundary {
+        x1: 0.0,
+        x2: 100.0,
+        y1: 0.0,
+        y2: 100.0,
+    };
+    let mut tree = create_quad_tree(boundary);
+
+    for op in &ops {
+        if !op.x.is_finite() || !op.y.is_finite() {
+            continue;
+        }
+        let point = quadtree::Point(op.x, op.y);
+        let was_in_bounds = contains(&boundary, point);
+        let inserted = insert(&mut tree, point);
+        assert_eq!(inserted, was_in_bounds);
+
+        if inserted {
+            let hits = search(&tree, &boundary);
+            assert!(hits.iter().any(|&p| p == point));
+        }
+    }
+
+    validate(&tree).expect("tree invariants must hold after any sequence of inserts");
+
+    let window = Boundary {
+        x1: 10.0,
+        x2: 15.0,
+        y1: 10.0,
+        y2: 15.0,
+    };
+    for point in search(&tree, &window) {
+        assert!(contains(&window, point));
+    }
+});
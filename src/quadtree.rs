@@ -3,43 +3,127 @@ use std::time::Instant;
 
 // A Point holds (x,y) coordinates for a location on earth
 // Usually these would be the latitude and longitude locations
-type Point = (f64, f64);
+// Point is a newtype over an (x, y) pair rather than a bare tuple, so a
+// function taking `Point` can't accidentally be handed an unrelated
+// `(f64, f64)` pair (a duration split, a grid cell index, etc). Field
+// access (`.0`, `.1`) is unchanged from the tuple it replaces.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub(crate) struct Point(pub(crate) f64, pub(crate) f64);
 
 // Boundary defines an enclosed rectangular area.
-struct Boundary {
-    x1: f64,
-    x2: f64,
-    y1: f64,
-    y2: f64,
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Boundary {
+    pub(crate) x1: f64,
+    pub(crate) x2: f64,
+    pub(crate) y1: f64,
+    pub(crate) y2: f64,
 }
 
+// f64 has no total ordering (NaN) so it can't derive Eq/Hash. Boundaries in
+// this codebase are always built from finite coordinates, so comparing and
+// hashing their bit patterns gives a well-behaved Eq/Hash pair that agrees
+// with the derived PartialEq an f64 comparison would give for those values,
+// except that `0.0` and `-0.0` have different bit patterns and so compare
+// unequal here.
+impl PartialEq for Boundary {
+    fn eq(&self, other: &Self) -> bool {
+        self.x1.to_bits() == other.x1.to_bits()
+            && self.x2.to_bits() == other.x2.to_bits()
+            && self.y1.to_bits() == other.y1.to_bits()
+            && self.y2.to_bits() == other.y2.to_bits()
+    }
+}
+
+impl Eq for Boundary {}
+
+impl std::hash::Hash for Boundary {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.x1.to_bits().hash(state);
+        self.x2.to_bits().hash(state);
+        self.y1.to_bits().hash(state);
+        self.y2.to_bits().hash(state);
+    }
+}
+
+// Quadrant identifies one of the four children of a node. The discriminants
+// match the order children are stored in `Quadtree::children`, so
+// `quadrant as usize` is always a valid index into it.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quadrant {
+    TopLeft = 0,
+    BottomLeft = 1,
+    TopRight = 2,
+    BottomRight = 3,
+}
+
+const QUADRANTS: [Quadrant; 4] = [
+    Quadrant::TopLeft,
+    Quadrant::BottomLeft,
+    Quadrant::TopRight,
+    Quadrant::BottomRight,
+];
+
+// INLINE_LEAF_CAPACITY is how many points `LeafPoints` stores inline,
+// before it falls back to a heap allocation, under the `smallvec_leaves`
+// feature.
+#[allow(dead_code)]
+const INLINE_LEAF_CAPACITY: usize = 4;
+
+// LeafPoints is `Quadtree::points`'s storage. Most leaves hold only a
+// handful of points, so with the `smallvec_leaves` feature enabled it
+// stores up to `INLINE_LEAF_CAPACITY` of them inline and only allocates on
+// the heap past that, cutting one allocation per leaf for the common
+// sparse case. Without the feature it's a plain `Vec`, so the dependency
+// isn't pulled in by default.
+#[cfg(feature = "smallvec_leaves")]
+type LeafPoints = smallvec::SmallVec<[Point; INLINE_LEAF_CAPACITY]>;
+#[cfg(not(feature = "smallvec_leaves"))]
+type LeafPoints = Vec<Point>;
+
 // Quadtree is a tree where each node in the tree will have exactly 4 children.
 // Each node will contain points upto 'MAX_CAPACITY'
 // Once the number of points in a node have reached capacity, the node will be subdivided into 4 child nodes and all the points will be distributed to the child nodes
-struct Quadtree {
+#[derive(Clone)]
+pub(crate) struct Quadtree {
     boundary: Boundary,
-    points: Vec<Point>,
-    top_left_child: Option<Box<Quadtree>>,
-    bottom_left_child: Option<Box<Quadtree>>,
-    top_right_child: Option<Box<Quadtree>>,
-    bottom_right_child: Option<Box<Quadtree>>,
+    points: LeafPoints,
+    // the four children, indexed by `Quadrant as usize`. `None` until the
+    // node subdivides.
+    children: Option<Box<[Quadtree; 4]>>,
+    // centroid of the points that were distributed to the children the last
+    // time this node was subdivided. Only internal (subdivided) nodes carry
+    // this; leaves are always `None`. `search` ignores it: it exists purely
+    // as a cheap representative point for algorithms that want a hybrid
+    // point-region tree without visiting every leaf.
+    internal_centroid: Option<Point>,
 }
 
 // maximum number of points that can be accomodated in a node before it subdivides into 4 child nodes
 const MAX_CAPACITY: usize = 100;
 
+// tolerance used when comparing two points for equality
+#[allow(dead_code)]
+const EPSILON: f64 = 1e-9;
+
+// returns true if the two points are the same location within EPSILON
+#[allow(dead_code)]
+fn points_equal(a: Point, b: Point) -> bool {
+    (a.0 - b.0).abs() <= EPSILON && (a.1 - b.1).abs() <= EPSILON
+}
+
 // Inserts a point in the Quadtree
 // If the number of points in the node are already at capacity, then the node will be subdivided in 4 child nodes
 // Post the sub-division the point will be added to the child node that it fits into
 // returns true if the point was inserted into the node or one of its child nodes
-fn insert(node: &mut Quadtree, point: Point) -> bool {
+pub(crate) fn insert(node: &mut Quadtree, point: Point) -> bool {
     // check if the point is outside the node's boundary, if yes then return false
     if !contains(&node.boundary, point) {
         return false;
     }
 
     // if node has not reached capacacity and has not been sub-divided, insert the point in this node
-    if node.points.len() < MAX_CAPACITY && node.top_left_child.is_none() {
+    if node.points.len() < MAX_CAPACITY && node.children.is_none() {
         node.points.push(point);
         return true;
     }
@@ -49,7 +133,7 @@ fn insert(node: &mut Quadtree, point: Point) -> bool {
     // 2. the node has reached its capacity but has not been sub-divided
 
     // if node has reached its capacity but has not yet been sub-divided, we need to sub-divide
-    if node.top_left_child.is_none() {
+    if node.children.is_none() {
         subdivide(node);
     }
 
@@ -57,18 +141,10 @@ fn insert(node: &mut Quadtree, point: Point) -> bool {
     // We can try inserting into all the child nodes
     // The node where the point's position is outside the boundary would
     // return false, until we find the correct child node.
-
-    if insert(node.top_left_child.as_mut().unwrap(), point) {
-        return true;
-    }
-    if insert(node.bottom_left_child.as_mut().unwrap(), point) {
-        return true;
-    }
-    if insert(node.top_right_child.as_mut().unwrap(), point) {
-        return true;
-    }
-    if insert(node.bottom_right_child.as_mut().unwrap(), point) {
-        return true;
+    for child in node.children.as_mut().unwrap().iter_mut() {
+        if insert(child, point) {
+            return true;
+        }
     }
 
     //we should not reach here
@@ -77,7 +153,7 @@ fn insert(node: &mut Quadtree, point: Point) -> bool {
 
 // check if a point is contained within the (x, y) co-ordinates
 // of the boundary's top-left and bottom-right corner
-fn contains(boundary: &Boundary, point: Point) -> bool {
+pub(crate) fn contains(boundary: &Boundary, point: Point) -> bool {
     point.0 >= boundary.x1
         && point.0 <= boundary.x2
         && point.1 >= boundary.y1
@@ -92,6 +168,16 @@ fn intersects(boundary_1: &Boundary, boundary_2: &Boundary) -> bool {
         && boundary_1.y2 >= boundary_2.y1
 }
 
+// creates an empty leaf node over the given boundary
+fn empty_node(boundary: Boundary) -> Quadtree {
+    Quadtree {
+        boundary,
+        points: LeafPoints::new(),
+        children: None,
+        internal_centroid: None,
+    }
+}
+
 // subdivide splits the node into 4 child nodes and moves the points in the node
 // to their correct child nodes
 fn subdivide(node: &mut Quadtree) {
@@ -103,88 +189,64 @@ fn subdivide(node: &mut Quadtree) {
     let mid_x = (x1 + x2) / 2.0;
     let mid_y = (y1 + y2) / 2.0;
 
-    node.top_left_child = Some(Box::new(Quadtree {
-        boundary: Boundary {
+    // order matches `Quadrant`'s discriminants: top-left, bottom-left,
+    // top-right, bottom-right
+    node.children = Some(Box::new([
+        empty_node(Boundary {
             x1,
             x2: mid_x,
             y1,
             y2: mid_y,
-        },
-        points: Vec::new(),
-        top_left_child: None,
-        bottom_left_child: None,
-        top_right_child: None,
-        bottom_right_child: None,
-    }));
-
-    node.bottom_left_child = Some(Box::new(Quadtree {
-        boundary: Boundary {
+        }),
+        empty_node(Boundary {
             x1,
             x2: mid_x,
             y1: mid_y,
             y2,
-        },
-        points: Vec::new(),
-        top_left_child: None,
-        bottom_left_child: None,
-        top_right_child: None,
-        bottom_right_child: None,
-    }));
-
-    node.top_right_child = Some(Box::new(Quadtree {
-        boundary: Boundary {
+        }),
+        empty_node(Boundary {
             x1: mid_x,
             x2,
             y1,
             y2: mid_y,
-        },
-        points: Vec::new(),
-        top_left_child: None,
-        bottom_left_child: None,
-        top_right_child: None,
-        bottom_right_child: None,
-    }));
-
-    node.bottom_right_child = Some(Box::new(Quadtree {
-        boundary: Boundary {
+        }),
+        empty_node(Boundary {
             x1: mid_x,
             x2,
             y1: mid_y,
             y2,
-        },
-        points: Vec::new(),
-        top_left_child: None,
-        bottom_left_child: None,
-        top_right_child: None,
-        bottom_right_child: None,
-    }));
+        }),
+    ]));
 
     // move points in the node to the child nodes that should contain the point.
     // we try inserting each point into all the child nodes.
     // if the position is outside the child node's boundary, insert will return false.
     // if insert returns true that means we have found our correct child node for that point.
-
-    let mut child_nodes = [
-        node.top_left_child.as_mut().unwrap(),
-        node.bottom_left_child.as_mut().unwrap(),
-        node.top_right_child.as_mut().unwrap(),
-        node.bottom_right_child.as_mut().unwrap(),
-    ];
-
+    let children = node.children.as_mut().unwrap();
     for point in &node.points {
-        for child_node in &mut child_nodes {
+        for child_node in children.iter_mut() {
             if insert(child_node, *point) {
                 break;
             }
         }
     }
 
+    // keep a representative centroid of the points that used to live here,
+    // for algorithms that want a cheap summary of this internal node without
+    // descending into its children
+    let count = node.points.len() as f64;
+    let sum = node
+        .points
+        .iter()
+        .fold((0.0, 0.0), |acc, p| (acc.0 + p.0, acc.1 + p.1));
+    node.internal_centroid = Some(Point(sum.0 / count, sum.1 / count));
+
     // no longer need points in the node
-    node.points = Vec::new();
+    node.points = LeafPoints::new();
 }
 
 // search returns all the points within the given boundary
-fn search(node: &Quadtree, boundary: &Boundary) -> Vec<Point> {
+pub(crate) fn search(node: &Quadtree, boundary: &Boundary) -> Vec<Point> {
     // if this node does not interesect with the search boundary
     // we know that the node and all its child nodes do not contain any points
     // that fall in the search boundary
@@ -194,139 +256,3327 @@ fn search(node: &Quadtree, boundary: &Boundary) -> Vec<Point> {
 
     // If this node has not yet been subdivided, return
     // all the points within the search boundary
-    if node.top_left_child.is_none() {
+    let Some(children) = &node.children else {
         return node
             .points
             .iter()
             .filter(|&point| contains(boundary, *point))
             .cloned()
             .collect();
-    }
+    };
 
     // If the node has been subdivided, search all
     // the child nodes and merge the results
     let mut result: Vec<Point> = Vec::new();
-    result.extend(search(node.top_left_child.as_ref().unwrap(), boundary));
-    result.extend(search(node.bottom_left_child.as_ref().unwrap(), boundary));
-    result.extend(search(node.top_right_child.as_ref().unwrap(), boundary));
-    result.extend(search(node.bottom_right_child.as_ref().unwrap(), boundary));
+    for child in children.iter() {
+        result.extend(search(child, boundary));
+    }
 
     result
 }
 
-// create the root node for the Quadtree
-fn create_quad_tree(boundary: Boundary) -> Quadtree {
-    Quadtree {
-        boundary,
-        points: Vec::new(),
-        top_left_child: None,
-        bottom_left_child: None,
-        top_right_child: None,
-        bottom_right_child: None,
+// contains_point returns true if the exact point (within EPSILON) is stored
+// somewhere in the tree
+#[allow(dead_code)]
+fn contains_point(node: &Quadtree, point: Point) -> bool {
+    if !contains(&node.boundary, point) {
+        return false;
     }
+
+    let Some(children) = &node.children else {
+        return node.points.iter().any(|&p| points_equal(p, point));
+    };
+
+    children.iter().any(|child| contains_point(child, point))
 }
 
-// naive search implementation
-// here points correspond to all the locations in our 2 dimnesional space
-// boundary represents the rectangular region
-// the function returns all the points contained in the rectangular region
-fn naive_search(points: &[Point], boundary: &Boundary) -> Vec<Point> {
-    points
+// missing returns the candidates that are not stored in the tree
+// this is useful for sync/diff workflows where you want to know which
+// points from a candidate set still need to be inserted
+#[allow(dead_code)]
+fn missing(node: &Quadtree, candidates: &[Point]) -> Vec<Point> {
+    candidates
         .iter()
-        .filter(|&point| contains(boundary, *point))
+        .filter(|&&point| !contains_point(node, point))
         .cloned()
         .collect()
 }
 
-fn main() {
-    // total points in our 2 dimensional space
-    //let total_points = 1_000_000; // 1 million
-    //let total_points = 10_000_000; // 10 million
-    let total_points = 100_000_000; // 100 million
-    println!(
-        "Total number of points in our 2 dimensional space {} ",
-        total_points
-    );
+// the following accessors expose read-only access to a node's children and
+// points so that callers can walk the tree and implement their own spatial
+// algorithms without forking the crate
 
-    // points vector will represent the list of points for our naive search
-    let mut points: Vec<Point> = Vec::new();
+#[allow(dead_code)]
+fn child(node: &Quadtree, quadrant: Quadrant) -> Option<&Quadtree> {
+    node.children.as_ref().map(|c| &c[quadrant as usize])
+}
 
-    // create the root node of the quad tree
-    // upper bound for x and y co-ordinates is 100
-    // lower bound for x and y co-ordinates is 0
-    let mut quadtree = create_quad_tree(Boundary {
-        x1: 0.0,
-        x2: 100.0,
-        y1: 0.0,
-        y2: 100.0,
-    });
+#[allow(dead_code)]
+fn top_left(node: &Quadtree) -> Option<&Quadtree> {
+    child(node, Quadrant::TopLeft)
+}
 
-    // initialize thread_rng()
-    let mut rng = rand::thread_rng();
+#[allow(dead_code)]
+fn bottom_left(node: &Quadtree) -> Option<&Quadtree> {
+    child(node, Quadrant::BottomLeft)
+}
 
-    let start_time = Instant::now();
-    // generate random points and add them to the points vector and quadtree
-    for _ in 0..total_points {
-        let x = rng.gen_range(0.0..=100.0);
-        let y = rng.gen_range(0.0..=100.0);
-        let point = (x, y);
+#[allow(dead_code)]
+fn top_right(node: &Quadtree) -> Option<&Quadtree> {
+    child(node, Quadrant::TopRight)
+}
 
-        points.push(point);
-        insert(&mut quadtree, point);
+#[allow(dead_code)]
+fn bottom_right(node: &Quadtree) -> Option<&Quadtree> {
+    child(node, Quadrant::BottomRight)
+}
+
+// points returns the points stored directly in this node (empty for a
+// subdivided node, since points move to the children)
+#[allow(dead_code)]
+fn points(node: &Quadtree) -> &[Point] {
+    &node.points
+}
+
+// validate checks the structural invariants of the tree and returns an
+// error describing the first violation found. This is a self-check used by
+// tests and fuzzing, and by callers after deserializing a tree from
+// untrusted storage.
+#[allow(dead_code)]
+pub(crate) fn validate(node: &Quadtree) -> Result<(), String> {
+    for &point in &node.points {
+        if !contains(&node.boundary, point) {
+            return Err(format!(
+                "point {:?} is not within its node's boundary [{}, {}] x [{}, {}]",
+                point, node.boundary.x1, node.boundary.x2, node.boundary.y1, node.boundary.y2
+            ));
+        }
     }
 
-    let elapsed_time = start_time.elapsed();
-    println!(
-        "Elapsed time for populating points and quadtree: {}s {}ms",
-        elapsed_time.as_secs(),
-        elapsed_time.subsec_millis()
-    );
+    let Some(children) = &node.children else {
+        return if node.points.len() > MAX_CAPACITY {
+            Err(format!(
+                "leaf holds {} points, above capacity {}",
+                node.points.len(),
+                MAX_CAPACITY
+            ))
+        } else {
+            Ok(())
+        };
+    };
 
-    // search for points within the specified Boundary using Quadtree
-    // here we are considering 10.0 and 15.0 as the lower and upper bounds for both x and y co-ordinates
-    // ideally this boundary will be defined based on the rectangular region we want to search w.r.t to a point.
-    let start_time = Instant::now();
-    println!(
-        "Quadtree search yielded {} points",
-        search(
-            &quadtree,
-            &Boundary {
-                x1: 10.0,
-                x2: 15.0,
-                y1: 10.0,
-                y2: 15.0,
-            },
-        )
-        .len()
-    );
+    if !node.points.is_empty() {
+        return Err("subdivided node still holds points directly".to_string());
+    }
 
-    let elapsed_time = start_time.elapsed();
-    println!(
-        "Elapsed time Quadtree search: {}s {}ms {} us",
-        elapsed_time.as_secs(),
-        elapsed_time.subsec_millis(),
-        elapsed_time.subsec_micros(),
-    );
+    let mid_x = (node.boundary.x1 + node.boundary.x2) / 2.0;
+    let mid_y = (node.boundary.y1 + node.boundary.y2) / 2.0;
+    let expected = [
+        (node.boundary.x1, mid_x, node.boundary.y1, mid_y),
+        (node.boundary.x1, mid_x, mid_y, node.boundary.y2),
+        (mid_x, node.boundary.x2, node.boundary.y1, mid_y),
+        (mid_x, node.boundary.x2, mid_y, node.boundary.y2),
+    ];
 
-    // search for points within the specified Boundary using naive search
-    let start_time = Instant::now();
-    println!(
-        "Naive search yielded {} points",
-        naive_search(
-            &points,
-            &Boundary {
-                x1: 10.0,
-                x2: 15.0,
-                y1: 10.0,
-                y2: 15.0,
-            },
+    for (child, (x1, x2, y1, y2)) in children.iter().zip(expected.iter()) {
+        if child.boundary.x1 != *x1
+            || child.boundary.x2 != *x2
+            || child.boundary.y1 != *y1
+            || child.boundary.y2 != *y2
+        {
+            return Err(format!(
+                "child boundary [{}, {}] x [{}, {}] does not exactly partition the parent",
+                child.boundary.x1, child.boundary.x2, child.boundary.y1, child.boundary.y2
+            ));
+        }
+        validate(child)?;
+    }
+
+    Ok(())
+}
+
+// search_with_paths behaves like `search` but annotates each returned point
+// with the sequence of quadrant choices leading to the leaf that holds it.
+// This is useful for debugging spatial addressing and for external indexes
+// that want to mirror the tree's layout.
+#[allow(dead_code)]
+fn search_with_paths(node: &Quadtree, boundary: &Boundary) -> Vec<(Point, Vec<Quadrant>)> {
+    let mut path = Vec::new();
+    let mut result = Vec::new();
+    search_with_paths_helper(node, boundary, &mut path, &mut result);
+    result
+}
+
+fn search_with_paths_helper(
+    node: &Quadtree,
+    boundary: &Boundary,
+    path: &mut Vec<Quadrant>,
+    result: &mut Vec<(Point, Vec<Quadrant>)>,
+) {
+    if !intersects(&node.boundary, boundary) {
+        return;
+    }
+
+    let Some(children) = &node.children else {
+        for &point in node.points.iter().filter(|&&p| contains(boundary, p)) {
+            result.push((point, path.clone()));
+        }
+        return;
+    };
+
+    for (quadrant, child) in QUADRANTS.iter().zip(children.iter()) {
+        path.push(*quadrant);
+        search_with_paths_helper(child, boundary, path, result);
+        path.pop();
+    }
+}
+
+// internal_centroid returns the centroid computed at subdivision time for
+// this node, or `None` if the node is a leaf that has never subdivided.
+// `search` never returns these points; they exist only as a representative
+// summary for callers implementing a hybrid point-region tree.
+#[allow(dead_code)]
+fn internal_centroid(node: &Quadtree) -> Option<Point> {
+    node.internal_centroid
+}
+
+// leaf_boxes returns every non-empty leaf as a bounding box paired with its
+// points, suitable for seeding an R-tree or as broad-phase collision boxes.
+// When `tight` is true the returned boundary is the minimal box enclosing
+// the leaf's points; otherwise it is the leaf's full allocated boundary.
+#[allow(dead_code)]
+fn leaf_boxes(node: &Quadtree, tight: bool) -> Vec<(Boundary, Vec<Point>)> {
+    let mut result = Vec::new();
+    leaf_boxes_helper(node, tight, &mut result);
+    result
+}
+
+fn leaf_boxes_helper(node: &Quadtree, tight: bool, result: &mut Vec<(Boundary, Vec<Point>)>) {
+    let Some(children) = &node.children else {
+        if node.points.is_empty() {
+            return;
+        }
+        let boundary = if tight {
+            let mut x1 = f64::INFINITY;
+            let mut x2 = f64::NEG_INFINITY;
+            let mut y1 = f64::INFINITY;
+            let mut y2 = f64::NEG_INFINITY;
+            for &Point(x, y) in &node.points {
+                x1 = x1.min(x);
+                x2 = x2.max(x);
+                y1 = y1.min(y);
+                y2 = y2.max(y);
+            }
+            Boundary { x1, x2, y1, y2 }
+        } else {
+            node.boundary
+        };
+        result.push((boundary, node.points.to_vec()));
+        return;
+    };
+
+    for child in children.iter() {
+        leaf_boxes_helper(child, tight, result);
+    }
+}
+
+// distance returns the euclidean distance between two points
+#[allow(dead_code)]
+fn distance(a: Point, b: Point) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+// search_radius returns all points within `radius` of `center`. It prunes
+// using the axis-aligned bounding box of the circle before filtering to the
+// exact circular region.
+#[allow(dead_code)]
+fn search_radius(node: &Quadtree, center: Point, radius: f64) -> Vec<Point> {
+    let bounding_box = Boundary {
+        x1: center.0 - radius,
+        x2: center.0 + radius,
+        y1: center.1 - radius,
+        y2: center.1 + radius,
+    };
+    search(node, &bounding_box)
+        .into_iter()
+        .filter(|&point| distance(point, center) <= radius)
+        .collect()
+}
+
+// density_at estimates local point density around `point`: the count of
+// points within `radius` divided by the circle's area. This gives a quick
+// kernel-density-style reading without building a full grid.
+#[allow(dead_code)]
+fn density_at(node: &Quadtree, point: Point, radius: f64) -> f64 {
+    let count = search_radius(node, point, radius).len() as f64;
+    let area = std::f64::consts::PI * radius * radius;
+    count / area
+}
+
+// quadkey produces a Bing-Maps-style string of `0`-`3` digits encoding the
+// quadrant descent for `point` down to `precision` levels, derived purely
+// from the node's boundary midpoints. The tree does not need to be
+// populated, or even subdivided, for this to work: digits are `0` top-left,
+// `1` bottom-left, `2` top-right, `3` bottom-right, matching `Quadrant`.
+#[allow(dead_code)]
+fn quadkey(node: &Quadtree, point: Point, precision: usize) -> String {
+    let mut boundary = node.boundary;
+    let mut key = String::with_capacity(precision);
+
+    for _ in 0..precision {
+        let mid_x = (boundary.x1 + boundary.x2) / 2.0;
+        let mid_y = (boundary.y1 + boundary.y2) / 2.0;
+
+        let (digit, next) = match (point.0 < mid_x, point.1 < mid_y) {
+            (true, true) => (
+                '0',
+                Boundary {
+                    x1: boundary.x1,
+                    x2: mid_x,
+                    y1: boundary.y1,
+                    y2: mid_y,
+                },
+            ),
+            (true, false) => (
+                '1',
+                Boundary {
+                    x1: boundary.x1,
+                    x2: mid_x,
+                    y1: mid_y,
+                    y2: boundary.y2,
+                },
+            ),
+            (false, true) => (
+                '2',
+                Boundary {
+                    x1: mid_x,
+                    x2: boundary.x2,
+                    y1: boundary.y1,
+                    y2: mid_y,
+                },
+            ),
+            (false, false) => (
+                '3',
+                Boundary {
+                    x1: mid_x,
+                    x2: boundary.x2,
+                    y1: mid_y,
+                    y2: boundary.y2,
+                },
+            ),
+        };
+
+        key.push(digit);
+        boundary = next;
+    }
+
+    key
+}
+
+// all_points collects every point stored anywhere in the tree
+#[allow(dead_code)]
+fn all_points(node: &Quadtree) -> Vec<Point> {
+    let mut result = Vec::new();
+    collect_all_points(node, &mut result);
+    result
+}
+
+fn collect_all_points(node: &Quadtree, result: &mut Vec<Point>) {
+    match &node.children {
+        Some(children) => {
+            for child in children.iter() {
+                collect_all_points(child, result);
+            }
+        }
+        None => result.extend(node.points.iter().cloned()),
+    }
+}
+
+// merge_from inserts every point from `other` into `node`. Points that fall
+// outside `node`'s boundary are skipped rather than causing an error; the
+// number skipped is returned so the caller can decide whether to grow the
+// boundary and retry.
+#[allow(dead_code)]
+fn merge_from(node: &mut Quadtree, other: &Quadtree) -> usize {
+    let mut skipped = 0;
+    for point in all_points(other) {
+        if !insert(node, point) {
+            skipped += 1;
+        }
+    }
+    skipped
+}
+
+// search_oriented_rect returns the points inside a rectangle centered at
+// `center` with the given `width`/`height`, rotated by `angle_rad`. It
+// prunes the tree using the rotated rectangle's axis-aligned bounding box,
+// then filters candidates by transforming them into the rectangle's local
+// (unrotated) frame.
+#[allow(dead_code)]
+fn search_oriented_rect(
+    node: &Quadtree,
+    center: Point,
+    width: f64,
+    height: f64,
+    angle_rad: f64,
+) -> Vec<Point> {
+    let half_width = width / 2.0;
+    let half_height = height / 2.0;
+    let cos_a = angle_rad.cos();
+    let sin_a = angle_rad.sin();
+
+    let local_corners = [
+        (-half_width, -half_height),
+        (half_width, -half_height),
+        (half_width, half_height),
+        (-half_width, half_height),
+    ];
+    let world_corners = local_corners.map(|(lx, ly)| {
+        (
+            center.0 + lx * cos_a - ly * sin_a,
+            center.1 + lx * sin_a + ly * cos_a,
         )
-        .len()
-    );
-    let elapsed_time = start_time.elapsed();
-    println!(
-        "Elapsed time Naive search: {}s {}ms",
-        elapsed_time.as_secs(),
-        elapsed_time.subsec_millis()
-    );
+    });
+
+    let bounding_box = Boundary {
+        x1: world_corners.iter().map(|p| p.0).fold(f64::INFINITY, f64::min),
+        x2: world_corners
+            .iter()
+            .map(|p| p.0)
+            .fold(f64::NEG_INFINITY, f64::max),
+        y1: world_corners.iter().map(|p| p.1).fold(f64::INFINITY, f64::min),
+        y2: world_corners
+            .iter()
+            .map(|p| p.1)
+            .fold(f64::NEG_INFINITY, f64::max),
+    };
+
+    search(node, &bounding_box)
+        .into_iter()
+        .filter(|&point| {
+            let dx = point.0 - center.0;
+            let dy = point.1 - center.1;
+            // rotate the point by -angle_rad to bring it into the
+            // rectangle's local, axis-aligned frame
+            let local_x = dx * cos_a + dy * sin_a;
+            let local_y = -dx * sin_a + dy * cos_a;
+            local_x.abs() <= half_width && local_y.abs() <= half_height
+        })
+        .collect()
+}
+
+// deepest_path returns the chain of node boundaries from the root down to
+// the deepest leaf, pinpointing where clustered data forced the most
+// subdivision.
+#[allow(dead_code)]
+fn deepest_path(node: &Quadtree) -> Vec<Boundary> {
+    let mut current = vec![node.boundary];
+    let mut best = current.clone();
+    deepest_path_helper(node, &mut current, &mut best);
+    best
+}
+
+fn deepest_path_helper(node: &Quadtree, current: &mut Vec<Boundary>, best: &mut Vec<Boundary>) {
+    if current.len() > best.len() {
+        *best = current.clone();
+    }
+
+    if let Some(children) = &node.children {
+        for child in children.iter() {
+            current.push(child.boundary);
+            deepest_path_helper(child, current, best);
+            current.pop();
+        }
+    }
+}
+
+// search_dedup behaves like `search` but collapses points that are
+// duplicates of each other (within EPSILON) into a single entry. Useful
+// when the tree's duplicate-insertion policy allows identical coordinates
+// but a query wants distinct locations.
+#[allow(dead_code)]
+fn search_dedup(node: &Quadtree, boundary: &Boundary) -> Vec<Point> {
+    let mut unique: Vec<Point> = Vec::new();
+    for point in search(node, boundary) {
+        if !unique.iter().any(|&u| points_equal(u, point)) {
+            unique.push(point);
+        }
+    }
+    unique
+}
+
+// Metrics accumulates cheap running counters for long-running services that
+// want observability into tree activity without external instrumentation.
+#[allow(dead_code)]
+#[derive(Debug, Default, Clone, Copy)]
+struct Metrics {
+    inserts_attempted: u64,
+    inserts_rejected: u64,
+    subdivisions: u64,
+}
+
+// insert_tracked behaves exactly like `insert`, but updates `metrics` as it
+// goes. Plain `insert` remains untouched and free of this bookkeeping for
+// callers who don't need it.
+#[allow(dead_code)]
+fn insert_tracked(node: &mut Quadtree, point: Point, metrics: &mut Metrics) -> bool {
+    metrics.inserts_attempted += 1;
+    let inserted = insert_tracked_recursive(node, point, metrics);
+    if !inserted {
+        metrics.inserts_rejected += 1;
+    }
+    inserted
+}
+
+fn insert_tracked_recursive(node: &mut Quadtree, point: Point, metrics: &mut Metrics) -> bool {
+    if !contains(&node.boundary, point) {
+        return false;
+    }
+
+    if node.points.len() < MAX_CAPACITY && node.children.is_none() {
+        node.points.push(point);
+        return true;
+    }
+
+    if node.children.is_none() {
+        subdivide(node);
+        metrics.subdivisions += 1;
+    }
+
+    for child in node.children.as_mut().unwrap().iter_mut() {
+        if insert_tracked_recursive(child, point, metrics) {
+            return true;
+        }
+    }
+
+    false
+}
+
+// boundary_contains returns true if `outer` fully encloses `inner`
+#[allow(dead_code)]
+fn boundary_contains(outer: &Boundary, inner: &Boundary) -> bool {
+    inner.x1 >= outer.x1 && inner.x2 <= outer.x2 && inner.y1 >= outer.y1 && inner.y2 <= outer.y2
+}
+
+// enclosing_node returns the deepest node whose boundary fully contains the
+// query boundary, letting callers restrict subsequent operations to that
+// subtree. Returns `None` if the query boundary is not fully contained by
+// the root.
+#[allow(dead_code)]
+fn enclosing_node<'a>(node: &'a Quadtree, boundary: &Boundary) -> Option<&'a Quadtree> {
+    if !boundary_contains(&node.boundary, boundary) {
+        return None;
+    }
+
+    let mut current = node;
+    while let Some(children) = &current.children {
+        match children
+            .iter()
+            .find(|child| boundary_contains(&child.boundary, boundary))
+        {
+            Some(child) => current = child,
+            None => break,
+        }
+    }
+
+    Some(current)
+}
+
+// Axis selects one of the two coordinate axes, used by line/edge queries.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+}
+
+// count_near_line counts points lying within `eps` of the line `axis ==
+// value` (e.g. `x == 50.0`). It prunes nodes whose boundary doesn't span
+// the line's tolerance band on that axis.
+#[allow(dead_code)]
+fn count_near_line(node: &Quadtree, axis: Axis, value: f64, eps: f64) -> usize {
+    let lo = value - eps;
+    let hi = value + eps;
+    let (boundary_lo, boundary_hi) = match axis {
+        Axis::X => (node.boundary.x1, node.boundary.x2),
+        Axis::Y => (node.boundary.y1, node.boundary.y2),
+    };
+    if boundary_hi < lo || boundary_lo > hi {
+        return 0;
+    }
+
+    match &node.children {
+        None => node
+            .points
+            .iter()
+            .filter(|&&point| {
+                let v = match axis {
+                    Axis::X => point.0,
+                    Axis::Y => point.1,
+                };
+                (v - value).abs() <= eps
+            })
+            .count(),
+        Some(children) => children
+            .iter()
+            .map(|child| count_near_line(child, axis, value, eps))
+            .sum(),
+    }
+}
+
+// tree_depth returns the maximum depth of the tree, where a leaf node has
+// depth 0.
+#[allow(dead_code)]
+fn tree_depth(node: &Quadtree) -> usize {
+    match &node.children {
+        None => 0,
+        Some(children) => 1 + children.iter().map(tree_depth).max().unwrap_or(0),
+    }
+}
+
+// rebuild collects every point in the tree and re-inserts them into a fresh
+// tree over the same boundary. This is the coarse hammer used to recover
+// from severe imbalance.
+#[allow(dead_code)]
+fn rebuild(node: &mut Quadtree) {
+    let points = all_points(node);
+    *node = empty_node(node.boundary);
+    for point in points {
+        insert(node, point);
+    }
+}
+
+// insert_auto_rebalance inserts like `insert`, but afterwards checks a
+// cheap imbalance heuristic and triggers `rebuild` when it's crossed: if
+// the tree's depth is more than 3x the depth a balanced tree holding this
+// many points would need (taken as `ceil(log4(n)) + 1`), the data is pathologically clustered
+// and worth flattening back out. The rebuild is O(n), so this amortizes
+// well as long as it doesn't trigger on every insert.
+#[allow(dead_code)]
+fn insert_auto_rebalance(node: &mut Quadtree, point: Point) -> bool {
+    let inserted = insert(node, point);
+    if inserted {
+        let count = all_points(node).len();
+        let expected_depth = (count as f64).log(4.0).ceil().max(0.0) as usize + 1;
+        if tree_depth(node) > expected_depth * 3 {
+            rebuild(node);
+        }
+    }
+    inserted
+}
+
+// search_multi walks the tree once, testing each node against every
+// boundary in `boundaries` and collecting per-boundary results. This
+// amortizes traversal cost for batch queries compared to calling `search`
+// once per window.
+#[allow(dead_code)]
+fn search_multi(node: &Quadtree, boundaries: &[Boundary]) -> Vec<Vec<Point>> {
+    let mut results = vec![Vec::new(); boundaries.len()];
+    search_multi_helper(node, boundaries, &mut results);
+    results
+}
+
+fn search_multi_helper(node: &Quadtree, boundaries: &[Boundary], results: &mut [Vec<Point>]) {
+    let relevant: Vec<usize> = boundaries
+        .iter()
+        .enumerate()
+        .filter(|(_, boundary)| intersects(&node.boundary, boundary))
+        .map(|(i, _)| i)
+        .collect();
+    if relevant.is_empty() {
+        return;
+    }
+
+    match &node.children {
+        None => {
+            for &i in &relevant {
+                for &point in node.points.iter().filter(|&&p| contains(&boundaries[i], p)) {
+                    results[i].push(point);
+                }
+            }
+        }
+        Some(children) => {
+            for child in children.iter() {
+                search_multi_helper(child, boundaries, results);
+            }
+        }
+    }
+}
+
+// Watch pairs a boundary with a callback to invoke whenever an insert
+// affects a point inside it. This supports reactive systems (e.g. a live
+// map re-rendering the visible window) without the tree itself knowing
+// anything about rendering.
+#[allow(dead_code)]
+struct Watch {
+    boundary: Boundary,
+    callback: Box<dyn FnMut(Point)>,
+}
+
+// WatchList holds the registered watches for a tree. It is kept separate
+// from `Quadtree` itself so that plain `insert`/`search` stay free of this
+// bookkeeping when watches aren't in use.
+#[allow(dead_code)]
+#[derive(Default)]
+struct WatchList {
+    watches: Vec<Watch>,
+}
+
+#[allow(dead_code)]
+fn watch(watches: &mut WatchList, boundary: Boundary, callback: impl FnMut(Point) + 'static) {
+    watches.watches.push(Watch {
+        boundary,
+        callback: Box::new(callback),
+    });
+}
+
+// insert_watched behaves like `insert`, additionally firing any registered
+// watch whose boundary contains the newly inserted point.
+#[allow(dead_code)]
+fn insert_watched(node: &mut Quadtree, point: Point, watches: &mut WatchList) -> bool {
+    let inserted = insert(node, point);
+    if inserted {
+        for watch in watches.watches.iter_mut() {
+            if contains(&watch.boundary, point) {
+                (watch.callback)(point);
+            }
+        }
+    }
+    inserted
+}
+
+// Display prints a one-line summary of the tree suitable for logging:
+// total point count, subtree count, and the root boundary.
+impl std::fmt::Display for Quadtree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Quadtree(points={}, nodes={}, boundary=[{}, {}] x [{}, {}])",
+            all_points(self).len(),
+            node_count(self),
+            self.boundary.x1,
+            self.boundary.x2,
+            self.boundary.y1,
+            self.boundary.y2
+        )
+    }
+}
+
+#[allow(dead_code)]
+fn node_count(node: &Quadtree) -> usize {
+    match &node.children {
+        None => 1,
+        Some(children) => 1 + children.iter().map(node_count).sum::<usize>(),
+    }
+}
+
+// extract_subtree returns an independent, owned copy of the smallest node
+// that fully encloses `boundary`, detached from the original tree. Callers
+// can insert into or search the returned tree without affecting `node`.
+#[allow(dead_code)]
+fn extract_subtree(node: &Quadtree, boundary: &Boundary) -> Option<Quadtree> {
+    enclosing_node(node, boundary).cloned()
+}
+
+// format_point renders a point with a caller-chosen number of decimal
+// places. Export formats (CSV, DOT, JSON, ...) that need to control output
+// size or precision should build on this rather than relying on the
+// default `f64` formatting.
+#[allow(dead_code)]
+fn format_point(point: Point, precision: usize) -> String {
+    format!("{:.*},{:.*}", precision, point.0, precision, point.1)
+}
+
+// export_points renders points one-per-line at the given precision, for
+// export formats that just need a flat point list.
+#[allow(dead_code)]
+fn export_points(points: &[Point], precision: usize) -> String {
+    points
+        .iter()
+        .map(|&p| format_point(p, precision))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// centroid computes the average position of every point currently stored
+// in the tree, unlike `internal_centroid` which only reflects the points a
+// single node redistributed at its last subdivision.
+#[allow(dead_code)]
+fn centroid(node: &Quadtree) -> Option<Point> {
+    let points = all_points(node);
+    if points.is_empty() {
+        return None;
+    }
+    let (sum_x, sum_y) = points
+        .iter()
+        .fold((0.0, 0.0), |(sx, sy), &Point(x, y)| (sx + x, sy + y));
+    let count = points.len() as f64;
+    Some(Point(sum_x / count, sum_y / count))
+}
+
+// leaf_occupancy lists every leaf's boundary together with how many points
+// it holds, without cloning the points themselves as `leaf_boxes` does.
+#[allow(dead_code)]
+fn leaf_occupancy(node: &Quadtree) -> Vec<(Boundary, usize)> {
+    let mut result = Vec::new();
+    leaf_occupancy_helper(node, &mut result);
+    result
+}
+
+fn leaf_occupancy_helper(node: &Quadtree, result: &mut Vec<(Boundary, usize)>) {
+    match &node.children {
+        None => result.push((node.boundary, node.points.len())),
+        Some(children) => {
+            for child in children.iter() {
+                leaf_occupancy_helper(child, result);
+            }
+        }
+    }
+}
+
+// root_boundary returns the boundary the tree was created with.
+#[allow(dead_code)]
+fn root_boundary(node: &Quadtree) -> Boundary {
+    node.boundary
+}
+
+// search_first is a fast path for callers that only need to know whether
+// any point exists in `boundary`, returning as soon as one is found
+// instead of collecting every match like `search` does.
+#[allow(dead_code)]
+fn search_first(node: &Quadtree, boundary: &Boundary) -> Option<Point> {
+    if !intersects(&node.boundary, boundary) {
+        return None;
+    }
+
+    let Some(children) = &node.children else {
+        return node.points.iter().copied().find(|&p| contains(boundary, p));
+    };
+
+    children.iter().find_map(|child| search_first(child, boundary))
+}
+
+// compact shrinks every leaf's point vector to fit its current length,
+// releasing capacity left over from bulk removals. Internal nodes are not
+// visited: `subdivide` already replaces their point vector with an empty one.
+#[allow(dead_code)]
+fn compact(node: &mut Quadtree) {
+    match &mut node.children {
+        None => node.points.shrink_to_fit(),
+        Some(children) => {
+            for child in children.iter_mut() {
+                compact(child);
+            }
+        }
+    }
+}
+
+// search_paginated returns up to `limit` points from `boundary`, plus a
+// continuation token to pass back in for the next page. `None` means there
+// are no more results. Traversal order is stable as long as the tree isn't
+// mutated between calls.
+#[allow(dead_code)]
+fn search_paginated(
+    node: &Quadtree,
+    boundary: &Boundary,
+    limit: usize,
+    token: Option<usize>,
+) -> (Vec<Point>, Option<usize>) {
+    let all = search(node, boundary);
+    let start = token.unwrap_or(0);
+    if start >= all.len() {
+        return (Vec::new(), None);
+    }
+
+    let end = (start + limit).min(all.len());
+    let page = all[start..end].to_vec();
+    let next = if end < all.len() { Some(end) } else { None };
+    (page, next)
+}
+
+// insert_flat is an alternative to `insert` for callers who want a flat
+// bucket rather than a subdivided tree: it never creates children and lets
+// the root's point vector grow past `MAX_CAPACITY`. Useful for small
+// datasets where the overhead of subdivision outweighs its benefit.
+#[allow(dead_code)]
+fn insert_flat(node: &mut Quadtree, point: Point) -> bool {
+    if !contains(&node.boundary, point) {
+        return false;
+    }
+    node.points.push(point);
+    true
+}
+
+// diff compares two tree snapshots taken at different points in time and
+// reports which points were added and which were removed between them.
+// Points are compared exactly, so re-inserting an identical point is a
+// no-op in the diff.
+#[allow(dead_code)]
+fn diff(old: &Quadtree, new: &Quadtree) -> (Vec<Point>, Vec<Point>) {
+    let mut old_points = all_points(old);
+    let mut new_points = all_points(new);
+    old_points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    new_points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let added = new_points
+        .iter()
+        .copied()
+        .filter(|p| old_points.binary_search_by(|q| q.partial_cmp(p).unwrap()).is_err())
+        .collect();
+    let removed = old_points
+        .iter()
+        .copied()
+        .filter(|p| new_points.binary_search_by(|q| q.partial_cmp(p).unwrap()).is_err())
+        .collect();
+
+    (added, removed)
+}
+
+// max_leaf_points returns the largest number of points held by any single
+// leaf, or 0 for an empty tree. Useful for spotting hotspots that keep
+// re-triggering subdivision without ever balancing out.
+#[allow(dead_code)]
+fn max_leaf_points(node: &Quadtree) -> usize {
+    match &node.children {
+        None => node.points.len(),
+        Some(children) => children.iter().map(max_leaf_points).max().unwrap_or(0),
+    }
+}
+
+// rebalance_to_depth rebuilds the tree from scratch, forcing subdivision
+// down to `target_depth` regardless of `MAX_CAPACITY`, then reinserts every
+// point. Unlike `rebuild`, which only re-subdivides where capacity demands
+// it, this guarantees every leaf is at least `target_depth` deep; a leaf
+// that then receives more than `MAX_CAPACITY` points still subdivides
+// further, so the depth is not uniform in that case.
+#[allow(dead_code)]
+fn rebalance_to_depth(node: &mut Quadtree, target_depth: usize) {
+    let points = all_points(node);
+    let boundary = node.boundary;
+    *node = empty_node(boundary);
+    force_subdivide_to_depth(node, target_depth);
+    for point in points {
+        insert(node, point);
+    }
+}
+
+fn force_subdivide_to_depth(node: &mut Quadtree, remaining_depth: usize) {
+    if remaining_depth == 0 {
+        return;
+    }
+    subdivide(node);
+    for child in node.children.as_mut().unwrap().iter_mut() {
+        force_subdivide_to_depth(child, remaining_depth - 1);
+    }
+}
+
+// PointF32 lets callers hold coordinates at half the memory of `Point`
+// (e.g. when staging a huge dataset before insertion). The tree itself
+// still stores `f64` internally, so `insert_f32`/`search_f32` only save
+// memory on the caller's side of the conversion, not inside the tree.
+#[allow(dead_code)]
+type PointF32 = (f32, f32);
+
+#[allow(dead_code)]
+fn to_f32(point: Point) -> PointF32 {
+    (point.0 as f32, point.1 as f32)
+}
+
+#[allow(dead_code)]
+fn from_f32(point: PointF32) -> Point {
+    Point(point.0 as f64, point.1 as f64)
+}
+
+#[allow(dead_code)]
+fn insert_f32(node: &mut Quadtree, point: PointF32) -> bool {
+    insert(node, from_f32(point))
+}
+
+#[allow(dead_code)]
+fn search_f32(node: &Quadtree, boundary: &Boundary) -> Vec<PointF32> {
+    search(node, boundary).into_iter().map(to_f32).collect()
+}
+
+// all_boundaries lists the boundary of every node in the tree, internal
+// nodes included, unlike `leaf_boxes` which only visits leaves.
+#[allow(dead_code)]
+fn all_boundaries(node: &Quadtree) -> Vec<Boundary> {
+    let mut result = vec![node.boundary];
+    if let Some(children) = &node.children {
+        for child in children.iter() {
+            result.extend(all_boundaries(child));
+        }
+    }
+    result
+}
+
+// nearest_neighbor_excluding finds the closest point to `query`, ignoring
+// any point equal to `query` itself. This is the common case when the
+// query point is already a member of the tree and callers want its
+// nearest neighbor rather than itself.
+#[allow(dead_code)]
+fn nearest_neighbor_excluding(node: &Quadtree, query: Point) -> Option<Point> {
+    all_points(node)
+        .into_iter()
+        .filter(|&p| !points_equal(p, query))
+        .min_by(|&a, &b| distance(query, a).partial_cmp(&distance(query, b)).unwrap())
+}
+
+// group_by_grid buckets every point into a coarse `cols` x `rows` grid
+// overlaid on the root boundary, independent of the tree's own
+// subdivisions. Returns one entry per non-empty cell, keyed by (col, row).
+#[allow(dead_code)]
+fn group_by_grid(node: &Quadtree, cols: usize, rows: usize) -> Vec<((usize, usize), Vec<Point>)> {
+    if cols == 0 || rows == 0 {
+        return Vec::new();
+    }
+
+    let boundary = node.boundary;
+    let cell_width = (boundary.x2 - boundary.x1) / cols as f64;
+    let cell_height = (boundary.y2 - boundary.y1) / rows as f64;
+
+    let mut groups: Vec<((usize, usize), Vec<Point>)> = Vec::new();
+    for point in all_points(node) {
+        let col = (((point.0 - boundary.x1) / cell_width) as usize).min(cols - 1);
+        let row = (((point.1 - boundary.y1) / cell_height) as usize).min(rows - 1);
+        match groups.iter_mut().find(|(key, _)| *key == (col, row)) {
+            Some((_, points)) => points.push(point),
+            None => groups.push(((col, row), vec![point])),
+        }
+    }
+    groups
+}
+
+// densest_region returns the boundary of the leaf holding the most points,
+// or `None` for an empty tree.
+#[allow(dead_code)]
+fn densest_region(node: &Quadtree) -> Option<Boundary> {
+    leaf_occupancy(node)
+        .into_iter()
+        .filter(|&(_, count)| count > 0)
+        .max_by_key(|&(_, count)| count)
+        .map(|(boundary, _)| boundary)
+}
+
+// insert_replacing inserts `point`, first removing any existing point
+// within `EPSILON` of it and returning that previous point. This gives
+// insert "replace" semantics for callers who treat coordinates as keys.
+#[allow(dead_code)]
+fn insert_replacing(node: &mut Quadtree, point: Point) -> Option<Point> {
+    let previous = remove_equal(node, point);
+    insert(node, point);
+    previous
+}
+
+fn remove_equal(node: &mut Quadtree, point: Point) -> Option<Point> {
+    if !contains(&node.boundary, point) {
+        return None;
+    }
+
+    if let Some(index) = node.points.iter().position(|&p| points_equal(p, point)) {
+        return Some(node.points.remove(index));
+    }
+
+    let Some(children) = &mut node.children else {
+        return None;
+    };
+    children.iter_mut().find_map(|child| remove_equal(child, point))
+}
+
+// coverage_ratio returns the fraction of the root's area occupied by
+// leaves that hold at least one point, in [0, 1].
+#[allow(dead_code)]
+fn coverage_ratio(node: &Quadtree) -> f64 {
+    let total_area = boundary_area(&node.boundary);
+    if total_area == 0.0 {
+        return 0.0;
+    }
+    let occupied_area: f64 = leaf_occupancy(node)
+        .into_iter()
+        .filter(|&(_, count)| count > 0)
+        .map(|(boundary, _)| boundary_area(&boundary))
+        .sum();
+    occupied_area / total_area
+}
+
+fn boundary_area(boundary: &Boundary) -> f64 {
+    (boundary.x2 - boundary.x1) * (boundary.y2 - boundary.y1)
+}
+
+// insert_with_path inserts `point` by following a precomputed sequence of
+// quadrants rather than trying each child in turn at every level,
+// subdividing as needed. Bulk loaders that already know a point's path
+// (e.g. from a quadkey computed offline) check only the chosen node's
+// boundary at each level; if the point is not inside it, this returns false.
+#[allow(dead_code)]
+fn insert_with_path(node: &mut Quadtree, point: Point, path: &[Quadrant]) -> bool {
+    if !contains(&node.boundary, point) {
+        return false;
+    }
+
+    let Some((&quadrant, rest)) = path.split_first() else {
+        debug_assert!(contains(&node.boundary, point));
+
+        // The path led to a node that has since been subdivided (e.g. by an
+        // unrelated insert), or one that is now at capacity. A subdivided
+        // node must never hold points directly, so fall back to `insert`'s
+        // own placement logic instead of pushing here.
+        if node.children.is_none() && node.points.len() < MAX_CAPACITY {
+            node.points.push(point);
+            return true;
+        }
+        return insert(node, point);
+    };
+
+    if node.children.is_none() {
+        subdivide(node);
+    }
+    let index = quadrant as usize;
+    insert_with_path(&mut node.children.as_mut().unwrap()[index], point, rest)
+}
+
+// EARTH_RADIUS_KM is used by `haversine_distance` and `nearest_neighbor_haversine`.
+#[allow(dead_code)]
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+// haversine_distance computes great-circle distance in kilometers between
+// two (latitude, longitude) points given in degrees.
+#[allow(dead_code)]
+fn haversine_distance(a: Point, b: Point) -> f64 {
+    let (lat1, lon1) = (a.0.to_radians(), a.1.to_radians());
+    let (lat2, lon2) = (b.0.to_radians(), b.1.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = lon2 - lon1;
+
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+// nearest_neighbor_haversine finds the point closest to `query` by
+// great-circle distance, for trees storing (latitude, longitude) points
+// rather than planar coordinates.
+#[allow(dead_code)]
+fn nearest_neighbor_haversine(node: &Quadtree, query: Point) -> Option<Point> {
+    all_points(node)
+        .into_iter()
+        .min_by(|&a, &b| {
+            haversine_distance(query, a)
+                .partial_cmp(&haversine_distance(query, b))
+                .unwrap()
+        })
+}
+
+// expand_boundary grows a boundary by `tolerance` on every side.
+#[allow(dead_code)]
+fn expand_boundary(boundary: &Boundary, tolerance: f64) -> Boundary {
+    Boundary {
+        x1: boundary.x1 - tolerance,
+        x2: boundary.x2 + tolerance,
+        y1: boundary.y1 - tolerance,
+        y2: boundary.y2 + tolerance,
+    }
+}
+
+// search_with_tolerance searches a boundary expanded by `tolerance` on
+// every side, useful when the caller wants a little slack around the
+// window (e.g. to catch points that are just outside due to floating
+// point rounding upstream).
+#[allow(dead_code)]
+fn search_with_tolerance(node: &Quadtree, boundary: &Boundary, tolerance: f64) -> Vec<Point> {
+    search(node, &expand_boundary(boundary, tolerance))
+}
+
+// to_dot renders the tree as a Graphviz DOT graph, one node per tree node
+// labeled with its boundary and point count, for visual debugging.
+#[allow(dead_code)]
+fn to_dot(node: &Quadtree) -> String {
+    let mut out = String::from("digraph Quadtree {\n");
+    let mut next_id = 0;
+    to_dot_helper(node, &mut out, &mut next_id);
+    out.push_str("}\n");
+    out
+}
+
+fn to_dot_helper(node: &Quadtree, out: &mut String, next_id: &mut usize) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+    out.push_str(&format!(
+        "  n{} [label=\"[{:.2}, {:.2}] x [{:.2}, {:.2}]\\npoints={}\"];\n",
+        id, node.boundary.x1, node.boundary.x2, node.boundary.y1, node.boundary.y2, node.points.len()
+    ));
+
+    if let Some(children) = &node.children {
+        for child in children.iter() {
+            let child_id = to_dot_helper(child, out, next_id);
+            out.push_str(&format!("  n{} -> n{};\n", id, child_id));
+        }
+    }
+
+    id
+}
+
+// insert_with_tie_break behaves like `insert`, except that when a point
+// sits exactly on a midline and matches more than one child's boundary,
+// it is placed in the first quadrant listed in `priority` rather than the
+// fixed `QUADRANTS` order.
+#[allow(dead_code)]
+fn insert_with_tie_break(node: &mut Quadtree, point: Point, priority: &[Quadrant]) -> bool {
+    if !contains(&node.boundary, point) {
+        return false;
+    }
+
+    if node.points.len() < MAX_CAPACITY && node.children.is_none() {
+        node.points.push(point);
+        return true;
+    }
+
+    if node.children.is_none() {
+        subdivide(node);
+    }
+
+    let children = node.children.as_mut().unwrap();
+    for &quadrant in priority {
+        if insert_with_tie_break(&mut children[quadrant as usize], point, priority) {
+            return true;
+        }
+    }
+    false
+}
+
+// count_points returns the total number of points in the tree by summing
+// leaf lengths, without cloning any point the way `all_points` does.
+#[allow(dead_code)]
+fn count_points(node: &Quadtree) -> usize {
+    match &node.children {
+        None => node.points.len(),
+        Some(children) => children.iter().map(count_points).sum(),
+    }
+}
+
+// parse_csv_points parses one "x,y" point per line, skipping blank lines.
+// Malformed lines are reported by their 1-based line number.
+#[allow(dead_code)]
+fn parse_csv_points(csv: &str) -> Result<Vec<Point>, String> {
+    csv.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let mut fields = line.split(',');
+            let x = fields.next().and_then(|s| s.trim().parse::<f64>().ok());
+            let y = fields.next().and_then(|s| s.trim().parse::<f64>().ok());
+            match (x, y, fields.next()) {
+                (Some(x), Some(y), None) => Ok(Point(x, y)),
+                _ => Err(format!("line {}: expected \"x,y\", got \"{}\"", i + 1, line)),
+            }
+        })
+        .collect()
+}
+
+// build_tree_from_csv parses `csv` and inserts every point into a new tree
+// bounded by `boundary`.
+#[allow(dead_code)]
+fn build_tree_from_csv(boundary: Boundary, csv: &str) -> Result<Quadtree, String> {
+    let points = parse_csv_points(csv)?;
+    let mut tree = create_quad_tree(boundary);
+    for point in points {
+        insert(&mut tree, point);
+    }
+    Ok(tree)
+}
+
+// nearest_neighbor_distances pairs every point in the tree with the
+// distance to its own nearest neighbor (excluding itself).
+#[allow(dead_code)]
+fn nearest_neighbor_distances(node: &Quadtree) -> Vec<(Point, f64)> {
+    let points = all_points(node);
+    points
+        .iter()
+        .map(|&p| {
+            let dist = points
+                .iter()
+                .filter(|&&q| !points_equal(p, q))
+                .map(|&q| distance(p, q))
+                .fold(f64::INFINITY, f64::min);
+            (p, dist)
+        })
+        .collect()
+}
+
+// is_region_populated reports whether every leaf overlapping `region` holds
+// at least one point, i.e. whether the region is fully "covered" by data
+// rather than partly empty space.
+#[allow(dead_code)]
+fn is_region_populated(node: &Quadtree, region: &Boundary) -> bool {
+    if !intersects(&node.boundary, region) {
+        return true;
+    }
+
+    match &node.children {
+        None => !node.points.is_empty(),
+        Some(children) => children.iter().all(|child| is_region_populated(child, region)),
+    }
+}
+
+// traverse_with_pruning visits every node depth-first, calling `visit` with
+// the node's boundary and point count. Returning `false` from `visit`
+// prunes that node's children, skipping the subtree entirely.
+#[allow(dead_code)]
+fn traverse_with_pruning(node: &Quadtree, visit: &mut impl FnMut(&Boundary, usize) -> bool) {
+    if !visit(&node.boundary, node.points.len()) {
+        return;
+    }
+    if let Some(children) = &node.children {
+        for child in children.iter() {
+            traverse_with_pruning(child, visit);
+        }
+    }
+}
+
+// convex_hull_in_region computes the convex hull (Andrew's monotone chain)
+// of the points found within `region`, returned counter-clockwise starting
+// from the leftmost point (the one with the lowest y among equal x).
+#[allow(dead_code)]
+fn convex_hull_in_region(node: &Quadtree, region: &Boundary) -> Vec<Point> {
+    let mut points = search(node, region);
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    points.dedup_by(|a, b| points_equal(*a, *b));
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    fn cross(o: Point, a: Point, b: Point) -> f64 {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    }
+
+    let mut lower = Vec::new();
+    for &p in &points {
+        while lower.len() >= 2 && cross(lower[lower.len() - 2], lower[lower.len() - 1], p) <= 0.0 {
+            lower.pop();
+        }
+        lower.push(p);
+    }
+
+    let mut upper = Vec::new();
+    for &p in points.iter().rev() {
+        while upper.len() >= 2 && cross(upper[upper.len() - 2], upper[upper.len() - 1], p) <= 0.0 {
+            upper.pop();
+        }
+        upper.push(p);
+    }
+
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+// bulk_insert_with_progress inserts every point in `points`, calling
+// `on_progress(inserted, total)` after each one so a caller can report
+// status (or resume from `inserted` later) during a large load.
+#[allow(dead_code)]
+fn bulk_insert_with_progress(
+    node: &mut Quadtree,
+    points: &[Point],
+    mut on_progress: impl FnMut(usize, usize),
+) {
+    let total = points.len();
+    for (i, &point) in points.iter().enumerate() {
+        insert(node, point);
+        on_progress(i + 1, total);
+    }
+}
+
+// neighbor_lists pairs every point with the other points within `radius`,
+// forming the spatial weight lists that autocorrelation statistics like
+// Moran's I are computed from.
+#[allow(dead_code)]
+fn neighbor_lists(node: &Quadtree, radius: f64) -> Vec<(Point, Vec<Point>)> {
+    all_points(node)
+        .into_iter()
+        .map(|p| (p, search_radius(node, p, radius).into_iter().filter(|&q| !points_equal(p, q)).collect()))
+        .collect()
+}
+
+// IdentifiedQuadtree pairs a Quadtree used for spatial queries with a
+// lookup table for retrieving a previously inserted point by an integer ID
+// the caller assigns, without changing what `Quadtree` itself stores.
+#[allow(dead_code)]
+struct IdentifiedQuadtree {
+    tree: Quadtree,
+    by_id: std::collections::HashMap<u64, Point>,
+}
+
+#[allow(dead_code)]
+fn create_identified_quad_tree(boundary: Boundary) -> IdentifiedQuadtree {
+    IdentifiedQuadtree {
+        tree: create_quad_tree(boundary),
+        by_id: std::collections::HashMap::new(),
+    }
+}
+
+#[allow(dead_code)]
+fn insert_with_id(tree: &mut IdentifiedQuadtree, id: u64, point: Point) -> bool {
+    if !insert(&mut tree.tree, point) {
+        return false;
+    }
+    tree.by_id.insert(id, point);
+    true
+}
+
+#[allow(dead_code)]
+fn point_by_id(tree: &IdentifiedQuadtree, id: u64) -> Option<Point> {
+    tree.by_id.get(&id).copied()
+}
+
+// search_progressive splits a search into one result batch per tree depth,
+// index 0 being the root. Points live only in leaves, so each batch holds
+// the matches from leaves at that depth; every level is collected before
+// the function returns, and no depth limit or representative points are
+// applied.
+#[allow(dead_code)]
+fn search_progressive(node: &Quadtree, boundary: &Boundary) -> Vec<Vec<Point>> {
+    let mut levels = Vec::new();
+    search_progressive_helper(node, boundary, 0, &mut levels);
+    levels
+}
+
+fn search_progressive_helper(node: &Quadtree, boundary: &Boundary, depth: usize, levels: &mut Vec<Vec<Point>>) {
+    if !intersects(&node.boundary, boundary) {
+        return;
+    }
+    if levels.len() <= depth {
+        levels.resize(depth + 1, Vec::new());
+    }
+    levels[depth].extend(node.points.iter().copied().filter(|&p| contains(boundary, p)));
+
+    if let Some(children) = &node.children {
+        for child in children.iter() {
+            search_progressive_helper(child, boundary, depth + 1, levels);
+        }
+    }
+}
+
+// retain drops every point for which `predicate` returns false, in place,
+// leaving the tree's subdivisions as they are.
+#[allow(dead_code)]
+fn retain(node: &mut Quadtree, predicate: &mut impl FnMut(Point) -> bool) {
+    let mut i = 0;
+    while i < node.points.len() {
+        if predicate(node.points[i]) {
+            i += 1;
+        } else {
+            node.points.remove(i);
+        }
+    }
+    if let Some(children) = &mut node.children {
+        for child in children.iter_mut() {
+            retain(child, predicate);
+        }
+    }
+}
+
+// to_json renders the tree as nested JSON by hand, without pulling in a
+// serialization crate. Each node has its boundary, points, and (if
+// subdivided) its four children in `QUADRANTS` order.
+#[allow(dead_code)]
+fn to_json(node: &Quadtree) -> String {
+    let boundary = format!(
+        "{{\"x1\":{},\"x2\":{},\"y1\":{},\"y2\":{}}}",
+        node.boundary.x1, node.boundary.x2, node.boundary.y1, node.boundary.y2
+    );
+    let points = node
+        .points
+        .iter()
+        .map(|p| format!("[{},{}]", p.0, p.1))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    match &node.children {
+        None => format!("{{\"boundary\":{},\"points\":[{}]}}", boundary, points),
+        Some(children) => {
+            let children_json = children.iter().map(to_json).collect::<Vec<_>>().join(",");
+            format!(
+                "{{\"boundary\":{},\"points\":[{}],\"children\":[{}]}}",
+                boundary, points, children_json
+            )
+        }
+    }
+}
+
+// insert_collecting_rejects inserts every point in `points` into the tree,
+// pushing any point that falls outside the root boundary into `rejected`
+// instead of silently dropping it.
+#[allow(dead_code)]
+fn insert_collecting_rejects(node: &mut Quadtree, points: &[Point], rejected: &mut Vec<Point>) {
+    for &point in points {
+        if !insert(node, point) {
+            rejected.push(point);
+        }
+    }
+}
+
+// loose_boundary expands a boundary around its own center by `factor`,
+// e.g. `factor = 2.0` doubles its width and height. Loose quadtrees use
+// this so a point near a child's edge can still match without needing to
+// be reinserted every time it drifts across the boundary.
+#[allow(dead_code)]
+fn loose_boundary(boundary: &Boundary, factor: f64) -> Boundary {
+    let center_x = (boundary.x1 + boundary.x2) / 2.0;
+    let center_y = (boundary.y1 + boundary.y2) / 2.0;
+    let half_width = (boundary.x2 - boundary.x1) / 2.0 * factor;
+    let half_height = (boundary.y2 - boundary.y1) / 2.0 * factor;
+    Boundary {
+        x1: center_x - half_width,
+        x2: center_x + half_width,
+        y1: center_y - half_height,
+        y2: center_y + half_height,
+    }
+}
+
+// insert_loose behaves like `insert`, but descends into a child whenever
+// the point falls within that child's boundary expanded by `factor`,
+// rather than only its tight boundary. This lets neighboring quadrants
+// overlap, trading exact partitioning for fewer boundary-crossing
+// re-insertions.
+#[allow(dead_code)]
+fn insert_loose(node: &mut Quadtree, point: Point, factor: f64) -> bool {
+    if !contains(&loose_boundary(&node.boundary, factor), point) {
+        return false;
+    }
+
+    if node.points.len() < MAX_CAPACITY && node.children.is_none() {
+        node.points.push(point);
+        return true;
+    }
+
+    if node.children.is_none() {
+        subdivide(node);
+    }
+
+    for child in node.children.as_mut().unwrap().iter_mut() {
+        if insert_loose(child, point, factor) {
+            return true;
+        }
+    }
+    false
+}
+
+// TimingStats accumulates wall-clock time spent in timed operations. Only
+// compiled in behind the `timing` feature, so builds without it pay no
+// cost at all.
+#[cfg(feature = "timing")]
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+struct TimingStats {
+    insert_time: std::time::Duration,
+    search_time: std::time::Duration,
+}
+
+#[cfg(feature = "timing")]
+#[allow(dead_code)]
+fn insert_timed(node: &mut Quadtree, point: Point, stats: &mut TimingStats) -> bool {
+    let start = Instant::now();
+    let result = insert(node, point);
+    stats.insert_time += start.elapsed();
+    result
+}
+
+#[cfg(feature = "timing")]
+#[allow(dead_code)]
+fn search_timed(node: &Quadtree, boundary: &Boundary, stats: &mut TimingStats) -> Vec<Point> {
+    let start = Instant::now();
+    let result = search(node, boundary);
+    stats.search_time += start.elapsed();
+    result
+}
+
+// closest_to_centroid returns the point stored in the tree that lies
+// nearest the centroid of all points, or `None` for an empty tree.
+#[allow(dead_code)]
+fn closest_to_centroid(node: &Quadtree) -> Option<Point> {
+    let center = centroid(node)?;
+    all_points(node)
+        .into_iter()
+        .min_by(|&a, &b| distance(center, a).partial_cmp(&distance(center, b)).unwrap())
+}
+
+// boundaries_equal compares two boundaries exactly, the equality
+// `search_cached` needs to recognize a repeated window.
+#[allow(dead_code)]
+fn boundaries_equal(a: &Boundary, b: &Boundary) -> bool {
+    a.x1 == b.x1 && a.x2 == b.x2 && a.y1 == b.y1 && a.y2 == b.y2
+}
+
+// search_cached reuses the previous search's results when `boundary`
+// exactly matches the last one queried, avoiding a repeat traversal for
+// callers that re-query the same window (e.g. a UI panning back and
+// forth). `cache` holds the last (boundary, results) pair.
+#[allow(dead_code)]
+fn search_cached(node: &Quadtree, boundary: &Boundary, cache: &mut Option<(Boundary, Vec<Point>)>) -> Vec<Point> {
+    if let Some((cached_boundary, cached_results)) = cache {
+        if boundaries_equal(cached_boundary, boundary) {
+            return cached_results.clone();
+        }
+    }
+
+    let results = search(node, boundary);
+    *cache = Some((*boundary, results.clone()));
+    results
+}
+
+// axis_imbalance reports, for each axis, how lopsided the point
+// distribution is relative to the root's midline: `(x_imbalance,
+// y_imbalance)`, each in [-1, 1] where 0 is perfectly balanced, positive
+// means more points on the high-coordinate side (right, and bottom in
+// `Quadrant`'s naming), and negative means more on the low side (left/top).
+#[allow(dead_code)]
+fn axis_imbalance(node: &Quadtree) -> (f64, f64) {
+    let points = all_points(node);
+    if points.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let mid_x = (node.boundary.x1 + node.boundary.x2) / 2.0;
+    let mid_y = (node.boundary.y1 + node.boundary.y2) / 2.0;
+    let total = points.len() as f64;
+
+    let high_x = points.iter().filter(|p| p.0 >= mid_x).count() as f64;
+    let high_y = points.iter().filter(|p| p.1 >= mid_y).count() as f64;
+
+    (
+        (2.0 * high_x / total) - 1.0,
+        (2.0 * high_y / total) - 1.0,
+    )
+}
+
+// nearest_neighbor_batch finds the nearest point to each query point,
+// preserving the order of `queries`.
+#[allow(dead_code)]
+fn nearest_neighbor_batch(node: &Quadtree, queries: &[Point]) -> Vec<Option<Point>> {
+    let points = all_points(node);
+    queries
+        .iter()
+        .map(|&query| {
+            points
+                .iter()
+                .min_by(|&&a, &&b| distance(query, a).partial_cmp(&distance(query, b)).unwrap())
+                .copied()
+        })
+        .collect()
+}
+
+// snap_to_grid rounds a point's coordinates to the nearest multiple of
+// `cell_size`.
+#[allow(dead_code)]
+fn snap_to_grid(point: Point, cell_size: f64) -> Point {
+    Point(
+        (point.0 / cell_size).round() * cell_size,
+        (point.1 / cell_size).round() * cell_size,
+    )
+}
+
+// insert_snapped quantizes `point` to the grid before inserting it,
+// letting many nearby points collapse onto the same cell.
+#[allow(dead_code)]
+fn insert_snapped(node: &mut Quadtree, point: Point, cell_size: f64) -> bool {
+    insert(node, snap_to_grid(point, cell_size))
+}
+
+// points_per_depth counts how many points are stored at each depth of the
+// tree, index 0 being the root.
+#[allow(dead_code)]
+fn points_per_depth(node: &Quadtree) -> Vec<usize> {
+    let mut histogram = Vec::new();
+    points_per_depth_helper(node, 0, &mut histogram);
+    histogram
+}
+
+fn points_per_depth_helper(node: &Quadtree, depth: usize, histogram: &mut Vec<usize>) {
+    if histogram.len() <= depth {
+        histogram.resize(depth + 1, 0);
+    }
+    histogram[depth] += node.points.len();
+
+    if let Some(children) = &node.children {
+        for child in children.iter() {
+            points_per_depth_helper(child, depth + 1, histogram);
+        }
+    }
+}
+
+// FrozenQuadtree is an immutable, flattened snapshot of a tree: every
+// leaf's points live contiguously in `points`, indexed by `leaf_offsets`.
+// Read-only queries scan leaf boundaries directly instead of recursing
+// through boxed children, trading the ability to insert for fewer
+// pointer-chasing allocations per query.
+#[allow(dead_code)]
+struct FrozenQuadtree {
+    leaves: Vec<Boundary>,
+    leaf_offsets: Vec<usize>,
+    points: Vec<Point>,
+}
+
+#[allow(dead_code)]
+fn freeze(node: Quadtree) -> FrozenQuadtree {
+    let leaf_boxes = leaf_boxes(&node, false);
+    let mut leaves = Vec::with_capacity(leaf_boxes.len());
+    let mut leaf_offsets = Vec::with_capacity(leaf_boxes.len() + 1);
+    let mut points = Vec::new();
+
+    leaf_offsets.push(0);
+    for (boundary, leaf_points) in leaf_boxes {
+        leaves.push(boundary);
+        points.extend(leaf_points);
+        leaf_offsets.push(points.len());
+    }
+
+    FrozenQuadtree { leaves, leaf_offsets, points }
+}
+
+#[allow(dead_code)]
+fn search_frozen(frozen: &FrozenQuadtree, boundary: &Boundary) -> Vec<Point> {
+    frozen
+        .leaves
+        .iter()
+        .enumerate()
+        .filter(|(_, leaf_boundary)| intersects(leaf_boundary, boundary))
+        .flat_map(|(i, _)| frozen.points[frozen.leaf_offsets[i]..frozen.leaf_offsets[i + 1]].iter().copied())
+        .filter(|&p| contains(boundary, p))
+        .collect()
+}
+
+// nearest_frozen finds the closest point to `query` in a frozen snapshot,
+// scanning the flattened point buffer directly rather than recursing
+// through boxed children.
+#[allow(dead_code)]
+fn nearest_frozen(frozen: &FrozenQuadtree, query: Point) -> Option<Point> {
+    frozen
+        .points
+        .iter()
+        .copied()
+        .min_by(|&a, &b| distance(query, a).partial_cmp(&distance(query, b)).unwrap())
+}
+
+// count_in_frozen counts the points inside `boundary` in a frozen
+// snapshot, without allocating a `Vec` of the matches like `search_frozen`.
+#[allow(dead_code)]
+fn count_in_frozen(frozen: &FrozenQuadtree, boundary: &Boundary) -> usize {
+    frozen
+        .leaves
+        .iter()
+        .enumerate()
+        .filter(|(_, leaf_boundary)| intersects(leaf_boundary, boundary))
+        .flat_map(|(i, _)| frozen.points[frozen.leaf_offsets[i]..frozen.leaf_offsets[i + 1]].iter().copied())
+        .filter(|&p| contains(boundary, p))
+        .count()
+}
+
+// search_excluding returns the points in `boundary` that do not also fall
+// within `exclude`.
+#[allow(dead_code)]
+fn search_excluding(node: &Quadtree, boundary: &Boundary, exclude: &Boundary) -> Vec<Point> {
+    search(node, boundary)
+        .into_iter()
+        .filter(|&p| !contains(exclude, p))
+        .collect()
+}
+
+// estimate_selectivity gives a cheap, approximate fraction of the tree's
+// points that a search over `boundary` would return, computed from the
+// overlap area between `boundary` and the root's boundary assuming a
+// roughly uniform distribution. It's meant for query planning, not an
+// exact count; call `search` for that.
+#[allow(dead_code)]
+fn estimate_selectivity(node: &Quadtree, boundary: &Boundary) -> f64 {
+    let root_area = boundary_area(&node.boundary);
+    if root_area == 0.0 {
+        return 0.0;
+    }
+
+    let overlap_x1 = node.boundary.x1.max(boundary.x1);
+    let overlap_x2 = node.boundary.x2.min(boundary.x2);
+    let overlap_y1 = node.boundary.y1.max(boundary.y1);
+    let overlap_y2 = node.boundary.y2.min(boundary.y2);
+
+    if overlap_x1 >= overlap_x2 || overlap_y1 >= overlap_y2 {
+        return 0.0;
+    }
+
+    ((overlap_x2 - overlap_x1) * (overlap_y2 - overlap_y1) / root_area).clamp(0.0, 1.0)
+}
+
+// BoundaryIter lazily walks a tree, yielding points inside a boundary one
+// at a time instead of collecting them all up front like `search` does.
+#[allow(dead_code)]
+struct BoundaryIter<'a> {
+    boundary: Boundary,
+    stack: Vec<&'a Quadtree>,
+    pending: std::slice::Iter<'a, Point>,
+}
+
+#[allow(dead_code)]
+fn iter_search(node: &Quadtree, boundary: Boundary) -> BoundaryIter<'_> {
+    BoundaryIter {
+        boundary,
+        stack: vec![node],
+        pending: [].iter(),
+    }
+}
+
+impl<'a> Iterator for BoundaryIter<'a> {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        loop {
+            if let Some(&point) = self.pending.next() {
+                if contains(&self.boundary, point) {
+                    return Some(point);
+                }
+                continue;
+            }
+
+            let node = self.stack.pop()?;
+            if !intersects(&node.boundary, &self.boundary) {
+                continue;
+            }
+            self.pending = node.points.iter();
+            if let Some(children) = &node.children {
+                self.stack.extend(children.iter());
+            }
+        }
+    }
+}
+
+// GridQuadtree covers its boundary with a `cols` x `rows` grid of
+// independent `Quadtree`s, one per cell, instead of the fixed 2x2 split
+// `subdivide` uses. Each cell subdivides on its own from there. Useful
+// when the data's aspect ratio doesn't match a square root boundary and a
+// non-square initial partition avoids a few wasted early subdivisions.
+#[allow(dead_code)]
+struct GridQuadtree {
+    boundary: Boundary,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Quadtree>,
+}
+
+#[allow(dead_code)]
+fn create_grid_quad_tree(boundary: Boundary, cols: usize, rows: usize) -> GridQuadtree {
+    let cell_width = (boundary.x2 - boundary.x1) / cols as f64;
+    let cell_height = (boundary.y2 - boundary.y1) / rows as f64;
+
+    let mut cells = Vec::with_capacity(cols * rows);
+    for row in 0..rows {
+        for col in 0..cols {
+            let x1 = boundary.x1 + col as f64 * cell_width;
+            let y1 = boundary.y1 + row as f64 * cell_height;
+            cells.push(empty_node(Boundary {
+                x1,
+                x2: x1 + cell_width,
+                y1,
+                y2: y1 + cell_height,
+            }));
+        }
+    }
+
+    GridQuadtree { boundary, cols, rows, cells }
+}
+
+#[allow(dead_code)]
+fn grid_insert(grid: &mut GridQuadtree, point: Point) -> bool {
+    if !contains(&grid.boundary, point) {
+        return false;
+    }
+    grid.cells.iter_mut().any(|cell| insert(cell, point))
+}
+
+#[allow(dead_code)]
+fn grid_search(grid: &GridQuadtree, boundary: &Boundary) -> Vec<Point> {
+    grid.cells.iter().flat_map(|cell| search(cell, boundary)).collect()
+}
+
+// distance_to_edge returns how far `point` is from the nearest edge of
+// `boundary`. Points outside the boundary give a negative distance to the
+// nearest edge they'd have to cross to get in.
+#[allow(dead_code)]
+fn distance_to_edge(boundary: &Boundary, point: Point) -> f64 {
+    let dx = (point.0 - boundary.x1).min(boundary.x2 - point.0);
+    let dy = (point.1 - boundary.y1).min(boundary.y2 - point.1);
+    dx.min(dy)
+}
+
+// sorted_by_edge_distance returns every point in the tree, sorted by how
+// close it is to the nearest edge of `boundary`, closest first.
+#[allow(dead_code)]
+fn sorted_by_edge_distance(node: &Quadtree, boundary: &Boundary) -> Vec<Point> {
+    let mut points = all_points(node);
+    points.sort_by(|&a, &b| {
+        distance_to_edge(boundary, a)
+            .partial_cmp(&distance_to_edge(boundary, b))
+            .unwrap()
+    });
+    points
+}
+
+// insert_reservoir inserts `point`, subdividing full leaves the same way
+// `insert` does. Only once a leaf is full *and* at `max_depth` (so it can
+// no longer subdivide) does it fall back to a ring-buffer: the oldest
+// point in that leaf is evicted to make room for the new one. This bounds
+// memory for streams where a max-depth leaf would otherwise grow forever,
+// while still building real spatial structure everywhere above that depth.
+#[allow(dead_code)]
+fn insert_reservoir(node: &mut Quadtree, point: Point, k: usize, max_depth: usize) -> bool {
+    if !contains(&node.boundary, point) {
+        return false;
+    }
+
+    if node.points.len() < k && node.children.is_none() {
+        node.points.push(point);
+        return true;
+    }
+
+    if node.children.is_none() {
+        if max_depth == 0 {
+            node.points.push(point);
+            let overflow = node.points.len() - k;
+            node.points.drain(0..overflow);
+            return true;
+        }
+        subdivide(node);
+    }
+
+    for child in node.children.as_mut().unwrap().iter_mut() {
+        if insert_reservoir(child, point, k, max_depth - 1) {
+            return true;
+        }
+    }
+    false
+}
+
+// bounding_boundary computes the smallest boundary that contains every
+// point in `points`, or `None` if `points` is empty.
+#[allow(dead_code)]
+fn bounding_boundary(points: &[Point]) -> Option<Boundary> {
+    let first = *points.first()?;
+    Some(points.iter().fold(
+        Boundary { x1: first.0, x2: first.0, y1: first.1, y2: first.1 },
+        |acc, &p| Boundary {
+            x1: acc.x1.min(p.0),
+            x2: acc.x2.max(p.0),
+            y1: acc.y1.min(p.1),
+            y2: acc.y2.max(p.1),
+        },
+    ))
+}
+
+// search_with_budget behaves like `search`, but stops visiting nodes once
+// `max_visits` nodes have been examined, returning whatever it found so
+// far along with whether the budget was exhausted before the whole
+// subtree was covered.
+#[allow(dead_code)]
+fn search_with_budget(node: &Quadtree, boundary: &Boundary, max_visits: usize) -> (Vec<Point>, bool) {
+    let mut results = Vec::new();
+    let mut visits = 0;
+    let exhausted = !search_with_budget_helper(node, boundary, max_visits, &mut visits, &mut results);
+    (results, exhausted)
+}
+
+// returns false once the budget runs out, short-circuiting the traversal.
+fn search_with_budget_helper(
+    node: &Quadtree,
+    boundary: &Boundary,
+    max_visits: usize,
+    visits: &mut usize,
+    results: &mut Vec<Point>,
+) -> bool {
+    if *visits >= max_visits {
+        return false;
+    }
+    *visits += 1;
+
+    if !intersects(&node.boundary, boundary) {
+        return true;
+    }
+    results.extend(node.points.iter().copied().filter(|&p| contains(boundary, p)));
+
+    let Some(children) = &node.children else {
+        return true;
+    };
+    children
+        .iter()
+        .all(|child| search_with_budget_helper(child, boundary, max_visits, visits, results))
+}
+
+// rotate_point rotates `point` by `angle_rad` radians counter-clockwise
+// around `pivot`.
+#[allow(dead_code)]
+fn rotate_point(point: Point, pivot: Point, angle_rad: f64) -> Point {
+    let dx = point.0 - pivot.0;
+    let dy = point.1 - pivot.1;
+    let (sin, cos) = angle_rad.sin_cos();
+    Point(pivot.0 + dx * cos - dy * sin, pivot.1 + dx * sin + dy * cos)
+}
+
+// rotate_dataset rebuilds the tree with every point rotated by `angle_rad`
+// radians around `pivot`, keeping the same root boundary. Points that
+// rotate outside the boundary are dropped, matching how `insert` already
+// rejects out-of-bounds points.
+#[allow(dead_code)]
+fn rotate_dataset(node: &mut Quadtree, pivot: Point, angle_rad: f64) {
+    let rotated: Vec<Point> = all_points(node)
+        .into_iter()
+        .map(|p| rotate_point(p, pivot, angle_rad))
+        .collect();
+    let boundary = node.boundary;
+    *node = empty_node(boundary);
+    for point in rotated {
+        insert(node, point);
+    }
+}
+
+// similarity returns the Jaccard index of two trees' point sets: the
+// fraction of the combined, deduplicated points that appear in both, in
+// [0, 1]. Identical trees score 1.0; disjoint ones score 0.0.
+#[allow(dead_code)]
+fn similarity(a: &Quadtree, b: &Quadtree) -> f64 {
+    let mut a_points = all_points(a);
+    let mut b_points = all_points(b);
+    a_points.sort_by(|p, q| p.partial_cmp(q).unwrap());
+    b_points.sort_by(|p, q| p.partial_cmp(q).unwrap());
+    a_points.dedup();
+    b_points.dedup();
+
+    let shared = a_points
+        .iter()
+        .filter(|&p| b_points.binary_search_by(|q| q.partial_cmp(p).unwrap()).is_ok())
+        .count();
+    let union = a_points.len() + b_points.len() - shared;
+    if union == 0 {
+        return 1.0;
+    }
+    shared as f64 / union as f64
+}
+
+// boundary_intersection returns the overlapping rectangle between two
+// boundaries, or `None` if they don't overlap.
+#[allow(dead_code)]
+fn boundary_intersection(a: &Boundary, b: &Boundary) -> Option<Boundary> {
+    let x1 = a.x1.max(b.x1);
+    let x2 = a.x2.min(b.x2);
+    let y1 = a.y1.max(b.y1);
+    let y2 = a.y2.min(b.y2);
+
+    if x1 > x2 || y1 > y2 {
+        return None;
+    }
+    Some(Boundary { x1, x2, y1, y2 })
+}
+
+// chebyshev_distance returns the chessboard distance between two points:
+// the greater of their axis-aligned differences.
+#[allow(dead_code)]
+fn chebyshev_distance(a: Point, b: Point) -> f64 {
+    (a.0 - b.0).abs().max((a.1 - b.1).abs())
+}
+
+// search_chebyshev finds every point within `radius` of `center` under
+// Chebyshev distance, i.e. inside the square centered on `center`. This is
+// exactly a boundary search, since a Chebyshev ball is a square.
+#[allow(dead_code)]
+fn search_chebyshev(node: &Quadtree, center: Point, radius: f64) -> Vec<Point> {
+    let boundary = Boundary {
+        x1: center.0 - radius,
+        x2: center.0 + radius,
+        y1: center.1 - radius,
+        y2: center.1 + radius,
+    };
+    search(node, &boundary)
+        .into_iter()
+        .filter(|&p| chebyshev_distance(center, p) <= radius)
+        .collect()
+}
+
+// top_n_by_metric returns the `n` points in `region` with the highest
+// `metric` value, descending. The tree doesn't carry a payload per point,
+// so callers supply `metric` to look one up (e.g. from a side table keyed
+// by point).
+#[allow(dead_code)]
+fn top_n_by_metric(node: &Quadtree, region: &Boundary, n: usize, metric: impl Fn(Point) -> f64) -> Vec<Point> {
+    let mut points = search(node, region);
+    points.sort_by(|&a, &b| metric(b).partial_cmp(&metric(a)).unwrap());
+    points.truncate(n);
+    points
+}
+
+// occupancy_bitmap overlays a `cols` x `rows` grid on the root boundary and
+// returns, row-major, whether each cell holds at least one point.
+#[allow(dead_code)]
+fn occupancy_bitmap(node: &Quadtree, cols: usize, rows: usize) -> Vec<bool> {
+    if cols == 0 || rows == 0 {
+        return Vec::new();
+    }
+
+    let mut bitmap = vec![false; cols * rows];
+    for ((col, row), _) in group_by_grid(node, cols, rows) {
+        bitmap[row * cols + col] = true;
+    }
+    bitmap
+}
+
+// spatial_partitions splits every point in the tree into `k` roughly
+// equal-sized groups that are spatially contiguous, by sorting points
+// along their quadkey (Z-order curve) before chunking. Nearby points share
+// similar quadkey prefixes, so each chunk covers a compact region rather
+// than being scattered across the whole boundary.
+#[allow(dead_code)]
+fn spatial_partitions(node: &Quadtree, k: usize) -> Vec<Vec<Point>> {
+    let mut points = all_points(node);
+    points.sort_by_key(|&p| quadkey(node, p, 16));
+
+    if k == 0 || points.is_empty() {
+        return Vec::new();
+    }
+    let chunk_size = points.len().div_ceil(k);
+    points.chunks(chunk_size).map(|chunk| chunk.to_vec()).collect()
+}
+
+// same_leaf reports whether `a` and `b` would be routed to the same leaf,
+// by descending the tree along each point's path and comparing where they
+// diverge.
+#[allow(dead_code)]
+fn same_leaf(node: &Quadtree, a: Point, b: Point) -> bool {
+    if !contains(&node.boundary, a) || !contains(&node.boundary, b) {
+        return false;
+    }
+
+    let Some(children) = &node.children else {
+        return true;
+    };
+
+    // like `insert`, a point goes to the first child whose boundary holds it
+    let route = |p: Point| children.iter().position(|child| contains(&child.boundary, p));
+    match (route(a), route(b)) {
+        (Some(i), Some(j)) if i == j => same_leaf(&children[i], a, b),
+        _ => false,
+    }
+}
+
+// OrderedQuadtree wraps a Quadtree with an insertion-order log, so callers
+// that need stable iteration (e.g. replaying inserts, or a UI that lists
+// points in the order they arrived) don't have to rely on the tree's
+// spatial traversal order, which shifts as subdivisions happen.
+#[allow(dead_code)]
+struct OrderedQuadtree {
+    tree: Quadtree,
+    insertion_order: Vec<Point>,
+}
+
+#[allow(dead_code)]
+fn create_ordered_quad_tree(boundary: Boundary) -> OrderedQuadtree {
+    OrderedQuadtree {
+        tree: create_quad_tree(boundary),
+        insertion_order: Vec::new(),
+    }
+}
+
+#[allow(dead_code)]
+fn insert_ordered(tree: &mut OrderedQuadtree, point: Point) -> bool {
+    if !insert(&mut tree.tree, point) {
+        return false;
+    }
+    tree.insertion_order.push(point);
+    true
+}
+
+// nearest_distance returns how far `query` is from the closest point in
+// the tree, without returning the point itself.
+#[allow(dead_code)]
+fn nearest_distance(node: &Quadtree, query: Point) -> Option<f64> {
+    all_points(node)
+        .into_iter()
+        .map(|p| distance(query, p))
+        .fold(None, |min, d| Some(min.map_or(d, |m: f64| m.min(d))))
+}
+
+// search_adaptive starts a search at `boundary` and, if it comes back
+// empty, keeps expanding it by `growth_factor` (via `loose_boundary`) up to
+// `max_attempts` times until it finds at least one point or gives up.
+#[allow(dead_code)]
+fn search_adaptive(node: &Quadtree, boundary: &Boundary, growth_factor: f64, max_attempts: usize) -> Vec<Point> {
+    let mut window = *boundary;
+    for _ in 0..=max_attempts {
+        let results = search(node, &window);
+        if !results.is_empty() {
+            return results;
+        }
+        window = loose_boundary(&window, growth_factor);
+    }
+    Vec::new()
+}
+
+// max_depth_resolution returns the (width, height) of a leaf at the
+// tree's deepest level, i.e. the smallest cell size the tree currently
+// distinguishes points at.
+#[allow(dead_code)]
+fn max_depth_resolution(node: &Quadtree) -> (f64, f64) {
+    let depth = tree_depth(node);
+    let width = (node.boundary.x2 - node.boundary.x1) / 2f64.powi(depth as i32);
+    let height = (node.boundary.y2 - node.boundary.y1) / 2f64.powi(depth as i32);
+    (width, height)
+}
+
+// search_map searches `boundary` and applies `transform` to each match in
+// the same pass, avoiding a separate `.into_iter().map(...)` allocation
+// over the intermediate `Vec<Point>`.
+#[allow(dead_code)]
+fn search_map<T>(node: &Quadtree, boundary: &Boundary, transform: impl Fn(Point) -> T) -> Vec<T> {
+    let mut results = Vec::new();
+    search_map_helper(node, boundary, &transform, &mut results);
+    results
+}
+
+fn search_map_helper<T>(node: &Quadtree, boundary: &Boundary, transform: &impl Fn(Point) -> T, results: &mut Vec<T>) {
+    if !intersects(&node.boundary, boundary) {
+        return;
+    }
+    results.extend(node.points.iter().copied().filter(|&p| contains(boundary, p)).map(transform));
+
+    if let Some(children) = &node.children {
+        for child in children.iter() {
+            search_map_helper(child, boundary, transform, results);
+        }
+    }
+}
+
+// find_duplicates reports every point that appears more than once in the
+// tree, along with how many times it occurs. Points are compared within
+// `EPSILON`, and only against their neighbours once sorted by (x, y).
+#[allow(dead_code)]
+fn find_duplicates(node: &Quadtree) -> Vec<(Point, usize)> {
+    let mut points = all_points(node);
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut duplicates = Vec::new();
+    let mut i = 0;
+    while i < points.len() {
+        let mut count = 1;
+        while i + count < points.len() && points_equal(points[i], points[i + count]) {
+            count += 1;
+        }
+        if count > 1 {
+            duplicates.push((points[i], count));
+        }
+        i += count;
+    }
+    duplicates
+}
+
+// clamp_to_boundary moves a point onto the boundary's edge if it lies
+// outside it, leaving points already inside untouched.
+#[allow(dead_code)]
+fn clamp_to_boundary(boundary: &Boundary, point: Point) -> Point {
+    Point(point.0.clamp(boundary.x1, boundary.x2), point.1.clamp(boundary.y1, boundary.y2))
+}
+
+// search_clipped searches a boundary expanded by `margin`, then clamps
+// every result back onto `boundary`'s edges. This is useful for rendering
+// a window where points just outside should still show up, snapped to the
+// edge, rather than being cut off entirely.
+#[allow(dead_code)]
+fn search_clipped(node: &Quadtree, boundary: &Boundary, margin: f64) -> Vec<Point> {
+    search(node, &expand_boundary(boundary, margin))
+        .into_iter()
+        .map(|p| clamp_to_boundary(boundary, p))
+        .collect()
+}
+
+// downsample_to_n returns at most `n` points from the tree, spread evenly
+// across occupied leaves rather than taken from one end of the flat point
+// list. Each leaf's point count sets its share of `n`, proportional
+// to how much of the tree it holds, then points within the leaf are
+// strided evenly, so the sample stays representative of every quadrant.
+#[allow(dead_code)]
+fn downsample_to_n(node: &Quadtree, n: usize) -> Vec<Point> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let leaves: Vec<Vec<Point>> = leaf_boxes(node, false)
+        .into_iter()
+        .map(|(_, points)| points)
+        .collect();
+
+    let total: usize = leaves.iter().map(Vec::len).sum();
+    if total <= n {
+        return leaves.into_iter().flatten().collect();
+    }
+
+    let mut sampled = Vec::with_capacity(n);
+    for points in &leaves {
+        let quota = points.len() * n / total;
+        for i in 0..quota {
+            let idx = i * points.len() / quota;
+            sampled.push(points[idx]);
+        }
+    }
+    sampled
+}
+
+// quadrant_boundaries computes the four child boundaries a node with this
+// boundary would have after subdividing, in `QUADRANTS` order, without
+// requiring the node to actually be subdivided.
+#[allow(dead_code)]
+fn quadrant_boundaries(boundary: &Boundary) -> [Boundary; 4] {
+    let x1 = boundary.x1;
+    let x2 = boundary.x2;
+    let y1 = boundary.y1;
+    let y2 = boundary.y2;
+    let mid_x = (x1 + x2) / 2.0;
+    let mid_y = (y1 + y2) / 2.0;
+
+    [
+        Boundary { x1, x2: mid_x, y1, y2: mid_y },
+        Boundary { x1, x2: mid_x, y1: mid_y, y2 },
+        Boundary { x1: mid_x, x2, y1, y2: mid_y },
+        Boundary { x1: mid_x, x2, y1: mid_y, y2 },
+    ]
+}
+
+// point_mass_at_depth aggregates the tree into a single (centroid, count)
+// "mass point" per node reached at `depth`, descending further into any
+// subtree that isn't yet that deep so every leaf is still represented.
+// This is meant for coarse-zoom rendering, where showing one dot per
+// cluster is cheaper than plotting every individual point.
+#[allow(dead_code)]
+fn point_mass_at_depth(node: &Quadtree, depth: usize) -> Vec<(Point, usize)> {
+    let mut result = Vec::new();
+    point_mass_at_depth_helper(node, depth, &mut result);
+    result
+}
+
+fn point_mass_at_depth_helper(node: &Quadtree, depth: usize, result: &mut Vec<(Point, usize)>) {
+    match &node.children {
+        Some(children) if depth > 0 => {
+            for child in children.iter() {
+                point_mass_at_depth_helper(child, depth - 1, result);
+            }
+        }
+        _ => {
+            if let Some(mass_center) = centroid(node) {
+                result.push((mass_center, all_points(node).len()));
+            }
+        }
+    }
+}
+
+// tree_contains_boundary returns true if the tree's own boundary fully
+// encloses the query boundary.
+#[allow(dead_code)]
+fn tree_contains_boundary(node: &Quadtree, boundary: &Boundary) -> bool {
+    boundary_contains(&node.boundary, boundary)
+}
+
+// OverflowStrategy selects what `build_streaming` does once `max_points`
+// have already been accepted and another point arrives.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverflowStrategy {
+    // evict the oldest point of the first non-empty leaf in a preorder
+    // walk (see `evict_oldest`) to make room, so the tree's total size
+    // never exceeds `max_points`.
+    Downsample,
+    // stop and report an error instead of accepting more points.
+    Error,
+}
+
+// evict_oldest removes the first point found in a preorder walk of the
+// tree's leaves, returning true if a point was removed. Used by
+// `build_streaming`'s `Downsample` strategy to make room for a new point
+// without growing the tree.
+fn evict_oldest(node: &mut Quadtree) -> bool {
+    match &mut node.children {
+        Some(children) => children.iter_mut().any(evict_oldest),
+        None => {
+            if node.points.is_empty() {
+                false
+            } else {
+                node.points.remove(0);
+                true
+            }
+        }
+    }
+}
+
+// build_streaming streams points from `source` into a new tree bounded by
+// `boundary`, accepting up to `max_points` normally. Once that cap is
+// reached, `overflow_strategy` decides what happens to the rest of the
+// stream: `Downsample` keeps the tree at `max_points` by evicting an old
+// point for every new one accepted, `Error` stops and reports the
+// overflow. This lets a caller index a stream larger than they can afford
+// to hold in memory in full.
+#[allow(dead_code)]
+fn build_streaming<I: Iterator<Item = Point>>(
+    boundary: Boundary,
+    source: I,
+    max_points: usize,
+    overflow_strategy: OverflowStrategy,
+) -> Result<Quadtree, String> {
+    let mut tree = create_quad_tree(boundary);
+    let mut accepted = 0;
+    for point in source {
+        if accepted < max_points {
+            if insert(&mut tree, point) {
+                accepted += 1;
+            }
+            continue;
+        }
+
+        match overflow_strategy {
+            OverflowStrategy::Error => {
+                return Err(format!("stream exceeded max_points ({max_points})"));
+            }
+            OverflowStrategy::Downsample => {
+                if max_points > 0 && contains(&boundary, point) {
+                    evict_oldest(&mut tree);
+                    insert(&mut tree, point);
+                }
+            }
+        }
+    }
+    Ok(tree)
+}
+
+// create the root node for the Quadtree
+pub(crate) fn create_quad_tree(boundary: Boundary) -> Quadtree {
+    empty_node(boundary)
+}
+
+// try_create_quad_tree is a checked alternative to create_quad_tree for
+// callers that build boundaries from untrusted input (e.g. deserialized
+// config) and want a Result instead of silently accepting a degenerate
+// or inverted boundary.
+#[allow(dead_code)]
+fn try_create_quad_tree(boundary: Boundary) -> Result<Quadtree, String> {
+    if boundary.x1 >= boundary.x2 || boundary.y1 >= boundary.y2 {
+        return Err(format!(
+            "invalid boundary: x1={}, x2={}, y1={}, y2={} must satisfy x1 < x2 and y1 < y2",
+            boundary.x1, boundary.x2, boundary.y1, boundary.y2
+        ));
+    }
+    Ok(empty_node(boundary))
+}
+
+// CompactQuadtree is an alternate representation for very large point sets.
+// Instead of every leaf owning a `Vec<Point>`, all points live once in a
+// single global `points` vector on the tree and each leaf only stores the
+// `u32` indices into it. This trades one extra indirection on search for a
+// much smaller per-leaf allocation, which matters once a node count is in
+// the hundreds of thousands.
+#[allow(dead_code)]
+struct CompactQuadtree {
+    root: CompactNode,
+    points: Vec<Point>,
+}
+
+// CompactNode's children, like `Quadtree`'s, are a `Quadrant`-indexed array
+// rather than four named fields, so insert/subdivide/search share the same
+// loop-over-children shape as the rest of the tree instead of repeating
+// four-way branches.
+#[allow(dead_code)]
+struct CompactNode {
+    boundary: Boundary,
+    point_indices: Vec<u32>,
+    children: Option<Box<[CompactNode; 4]>>,
+}
+
+fn empty_compact_node(boundary: Boundary) -> CompactNode {
+    CompactNode {
+        boundary,
+        point_indices: Vec::new(),
+        children: None,
+    }
+}
+
+// create_compact_quad_tree creates an empty CompactQuadtree over the given boundary
+#[allow(dead_code)]
+fn create_compact_quad_tree(boundary: Boundary) -> CompactQuadtree {
+    CompactQuadtree {
+        root: empty_compact_node(boundary),
+        points: Vec::new(),
+    }
+}
+
+// compact_insert stores the point in the tree's global point vector and
+// inserts its index into the correct node/leaf. Returns true if the point
+// was within the root boundary and got inserted.
+#[allow(dead_code)]
+fn compact_insert(tree: &mut CompactQuadtree, point: Point) -> bool {
+    if !contains(&tree.root.boundary, point) {
+        return false;
+    }
+    let index = tree.points.len() as u32;
+    tree.points.push(point);
+    compact_insert_index(&tree.points, &mut tree.root, index)
+}
+
+// compact_insert_index inserts a point index into a node, subdividing the
+// node when it is at capacity. `points` is the shared global point vector,
+// used to resolve indices to coordinates when subdividing.
+fn compact_insert_index(points: &[Point], node: &mut CompactNode, index: u32) -> bool {
+    if !contains(&node.boundary, points[index as usize]) {
+        return false;
+    }
+
+    if node.point_indices.len() < MAX_CAPACITY && node.children.is_none() {
+        node.point_indices.push(index);
+        return true;
+    }
+
+    if node.children.is_none() {
+        compact_subdivide(points, node);
+    }
+
+    for child in node.children.as_mut().unwrap().iter_mut() {
+        if compact_insert_index(points, child, index) {
+            return true;
+        }
+    }
+
+    false
+}
+
+// compact_subdivide splits a compact node into 4 child nodes and redistributes
+// its point indices, mirroring `subdivide`.
+fn compact_subdivide(points: &[Point], node: &mut CompactNode) {
+    let bounds = quadrant_boundaries(&node.boundary);
+    node.children = Some(Box::new([
+        empty_compact_node(bounds[0]),
+        empty_compact_node(bounds[1]),
+        empty_compact_node(bounds[2]),
+        empty_compact_node(bounds[3]),
+    ]));
+
+    for index in std::mem::take(&mut node.point_indices) {
+        for child in node.children.as_mut().unwrap().iter_mut() {
+            if compact_insert_index(points, child, index) {
+                break;
+            }
+        }
+    }
+}
+
+// compact_search returns the indices (into the tree's global point vector)
+// of all points within the given boundary. Resolve them with `tree.points`.
+#[allow(dead_code)]
+fn compact_search(tree: &CompactQuadtree, boundary: &Boundary) -> Vec<u32> {
+    compact_search_node(&tree.root, &tree.points, boundary)
+}
+
+fn compact_search_node(node: &CompactNode, points: &[Point], boundary: &Boundary) -> Vec<u32> {
+    if !intersects(&node.boundary, boundary) {
+        return vec![];
+    }
+
+    let Some(children) = &node.children else {
+        return node
+            .point_indices
+            .iter()
+            .filter(|&&index| contains(boundary, points[index as usize]))
+            .cloned()
+            .collect();
+    };
+
+    let mut result = Vec::new();
+    for child in children.iter() {
+        result.extend(compact_search_node(child, points, boundary));
+    }
+    result
+}
+
+// benchmark_compact_vs_quadtree times inserting and searching `count`
+// random points into both a `Quadtree` and a `CompactQuadtree` over the
+// same boundary, printing elapsed time for each so the two representations
+// can be compared. Not wired into `main`; run it manually when evaluating
+// whether the compact representation is worth its extra indirection on
+// search for a given workload.
+#[allow(dead_code)]
+fn benchmark_compact_vs_quadtree(boundary: Boundary, count: usize) {
+    let mut rng = rand::thread_rng();
+    let points: Vec<Point> = (0..count)
+        .map(|_| {
+            Point(
+                rng.gen_range(boundary.x1..boundary.x2),
+                rng.gen_range(boundary.y1..boundary.y2),
+            )
+        })
+        .collect();
+
+    let start = Instant::now();
+    let mut tree = create_quad_tree(boundary);
+    for &point in &points {
+        insert(&mut tree, point);
+    }
+    println!("Quadtree insert of {count} points: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    search(&tree, &boundary);
+    println!("Quadtree search: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let mut compact = create_compact_quad_tree(boundary);
+    for &point in &points {
+        compact_insert(&mut compact, point);
+    }
+    println!("CompactQuadtree insert of {count} points: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    compact_search(&compact, &boundary);
+    println!("CompactQuadtree search: {:?}", start.elapsed());
+}
+
+// naive search implementation
+// here points correspond to all the locations in our 2 dimnesional space
+// boundary represents the rectangular region
+// the function returns all the points contained in the rectangular region
+fn naive_search(points: &[Point], boundary: &Boundary) -> Vec<Point> {
+    points
+        .iter()
+        .filter(|&point| contains(boundary, *point))
+        .cloned()
+        .collect()
+}
+
+fn main() {
+    // total points in our 2 dimensional space
+    //let total_points = 1_000_000; // 1 million
+    //let total_points = 10_000_000; // 10 million
+    let total_points = 100_000_000; // 100 million
+    println!(
+        "Total number of points in our 2 dimensional space {} ",
+        total_points
+    );
+
+    // points vector will represent the list of points for our naive search
+    let mut points: Vec<Point> = Vec::new();
+
+    // create the root node of the quad tree
+    // upper bound for x and y co-ordinates is 100
+    // lower bound for x and y co-ordinates is 0
+    let mut quadtree = create_quad_tree(Boundary {
+        x1: 0.0,
+        x2: 100.0,
+        y1: 0.0,
+        y2: 100.0,
+    });
+
+    // initialize thread_rng()
+    let mut rng = rand::thread_rng();
+
+    let start_time = Instant::now();
+    // generate random points and add them to the points vector and quadtree
+    for _ in 0..total_points {
+        let x = rng.gen_range(0.0..=100.0);
+        let y = rng.gen_range(0.0..=100.0);
+        let point = Point(x, y);
+
+        points.push(point);
+        insert(&mut quadtree, point);
+    }
+
+    let elapsed_time = start_time.elapsed();
+    println!(
+        "Elapsed time for populating points and quadtree: {}s {}ms",
+        elapsed_time.as_secs(),
+        elapsed_time.subsec_millis()
+    );
+
+    // search for points within the specified Boundary using Quadtree
+    // here we are considering 10.0 and 15.0 as the lower and upper bounds for both x and y co-ordinates
+    // ideally this boundary will be defined based on the rectangular region we want to search w.r.t to a point.
+    let start_time = Instant::now();
+    println!(
+        "Quadtree search yielded {} points",
+        search(
+            &quadtree,
+            &Boundary {
+                x1: 10.0,
+                x2: 15.0,
+                y1: 10.0,
+                y2: 15.0,
+            },
+        )
+        .len()
+    );
+
+    let elapsed_time = start_time.elapsed();
+    println!(
+        "Elapsed time Quadtree search: {}s {}ms {} us",
+        elapsed_time.as_secs(),
+        elapsed_time.subsec_millis(),
+        elapsed_time.subsec_micros(),
+    );
+
+    // search for points within the specified Boundary using naive search
+    let start_time = Instant::now();
+    println!(
+        "Naive search yielded {} points",
+        naive_search(
+            &points,
+            &Boundary {
+                x1: 10.0,
+                x2: 15.0,
+                y1: 10.0,
+                y2: 15.0,
+            },
+        )
+        .len()
+    );
+    let elapsed_time = start_time.elapsed();
+    println!(
+        "Elapsed time Naive search: {}s {}ms",
+        elapsed_time.as_secs(),
+        elapsed_time.subsec_millis()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_tree() -> Quadtree {
+        let boundary = Boundary { x1: 0.0, x2: 100.0, y1: 0.0, y2: 100.0 };
+        let mut tree = create_quad_tree(boundary);
+        for i in 0..14 {
+            for j in 0..14 {
+                insert(&mut tree, Point(i as f64 * 7.0 + 1.0, j as f64 * 5.0 + 2.0));
+            }
+        }
+        tree
+    }
+
+    #[test]
+    fn freeze_matches_mutable_tree_queries() {
+        let tree = sample_tree();
+        let window = Boundary { x1: 10.0, x2: 60.0, y1: 10.0, y2: 60.0 };
+        let query = Point(42.3, 17.7);
+
+        let mut expected_search = search(&tree, &window);
+        let expected_count = expected_search.len();
+        let expected_nearest = all_points(&tree)
+            .into_iter()
+            .min_by(|&a, &b| distance(query, a).partial_cmp(&distance(query, b)).unwrap());
+
+        let frozen = freeze(tree);
+        let mut actual_search = search_frozen(&frozen, &window);
+
+        expected_search.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        actual_search.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(expected_search, actual_search);
+        assert_eq!(count_in_frozen(&frozen, &window), expected_count);
+        assert_eq!(nearest_frozen(&frozen, query), expected_nearest);
+    }
+
+    #[test]
+    fn build_streaming_downsample_caps_total_points() {
+        let boundary = Boundary { x1: 0.0, x2: 100.0, y1: 0.0, y2: 100.0 };
+        let max_points = 50;
+        let source = (0..500).map(|i| Point((i % 100) as f64, ((i * 7) % 100) as f64));
+
+        let tree = build_streaming(boundary, source, max_points, OverflowStrategy::Downsample)
+            .expect("downsample never errors");
+
+        assert!(count_points(&tree) <= max_points);
+    }
+
+    #[test]
+    fn build_streaming_error_stops_on_overflow() {
+        let boundary = Boundary { x1: 0.0, x2: 100.0, y1: 0.0, y2: 100.0 };
+        let max_points = 10;
+        let source = (0..50).map(|i| Point((i % 100) as f64, ((i * 7) % 100) as f64));
+
+        let result = build_streaming(boundary, source, max_points, OverflowStrategy::Error);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn missing_reports_only_absent_candidates() {
+        let tree = sample_tree();
+        let present = Point(1.0, 2.0);
+        let absent = Point(500.0, 500.0);
+
+        let result = missing(&tree, &[present, absent]);
+
+        assert_eq!(result, vec![absent]);
+    }
+
+    #[test]
+    fn validate_catches_a_point_outside_its_leaf_boundary() {
+        let mut tree = sample_tree();
+        assert!(validate(&tree).is_ok());
+
+        tree.points.push(Point(-999.0, -999.0));
+        assert!(validate(&tree).is_err());
+    }
+
+    #[test]
+    fn search_with_paths_leads_to_the_correct_leaf() {
+        let tree = sample_tree();
+        let window = tree.boundary;
+
+        for (point, path) in search_with_paths(&tree, &window) {
+            let mut node = &tree;
+            for quadrant in &path {
+                node = &node.children.as_ref().unwrap()[*quadrant as usize];
+            }
+            assert!(contains(&node.boundary, point));
+            assert!(node.children.is_none());
+        }
+    }
+
+    #[test]
+    fn merge_from_combines_two_trees_points() {
+        let mut a = create_quad_tree(Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 });
+        let mut b = create_quad_tree(Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 });
+        insert(&mut a, Point(1.0, 1.0));
+        insert(&mut b, Point(2.0, 2.0));
+
+        let skipped = merge_from(&mut a, &b);
+
+        assert_eq!(skipped, 0);
+        assert_eq!(count_points(&a), 2);
+    }
+
+    #[test]
+    fn search_dedup_collapses_identical_points() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        insert(&mut tree, Point(5.0, 5.0));
+        insert(&mut tree, Point(5.0, 5.0));
+
+        assert_eq!(search(&tree, &boundary).len(), 2);
+        assert_eq!(search_dedup(&tree, &boundary).len(), 1);
+    }
+
+    #[test]
+    fn count_near_line_counts_points_on_and_near_the_line() {
+        let boundary = Boundary { x1: 0.0, x2: 100.0, y1: 0.0, y2: 100.0 };
+        let mut tree = create_quad_tree(boundary);
+        insert(&mut tree, Point(50.0, 10.0));
+        insert(&mut tree, Point(50.001, 20.0));
+        insert(&mut tree, Point(90.0, 30.0));
+
+        assert_eq!(count_near_line(&tree, Axis::X, 50.0, 0.01), 2);
+    }
+
+    #[test]
+    fn try_create_quad_tree_rejects_an_inverted_boundary() {
+        let inverted = Boundary { x1: 10.0, x2: 0.0, y1: 0.0, y2: 10.0 };
+        assert!(try_create_quad_tree(inverted).is_err());
+
+        let valid = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        assert!(try_create_quad_tree(valid).is_ok());
+    }
+
+    #[test]
+    fn centroid_averages_the_points_in_the_tree() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        insert(&mut tree, Point(0.0, 0.0));
+        insert(&mut tree, Point(10.0, 0.0));
+
+        assert_eq!(centroid(&tree), Some(Point(5.0, 0.0)));
+    }
+
+    #[test]
+    fn diff_reports_added_and_removed_points() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut old = create_quad_tree(boundary);
+        insert(&mut old, Point(1.0, 1.0));
+        insert(&mut old, Point(2.0, 2.0));
+
+        let mut new = create_quad_tree(boundary);
+        insert(&mut new, Point(2.0, 2.0));
+        insert(&mut new, Point(3.0, 3.0));
+
+        let (added, removed) = diff(&old, &new);
+
+        assert_eq!(added, vec![Point(3.0, 3.0)]);
+        assert_eq!(removed, vec![Point(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn nearest_neighbor_excluding_skips_the_query_point() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        let query = Point(5.0, 5.0);
+        insert(&mut tree, query);
+        insert(&mut tree, Point(5.1, 5.0));
+
+        assert_eq!(nearest_neighbor_excluding(&tree, query), Some(Point(5.1, 5.0)));
+    }
+
+    #[test]
+    fn insert_replacing_returns_and_replaces_the_previous_point() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        insert(&mut tree, Point(3.0, 3.0));
+
+        let previous = insert_replacing(&mut tree, Point(3.0, 3.0));
+
+        assert_eq!(previous, Some(Point(3.0, 3.0)));
+        assert_eq!(count_points(&tree), 1);
+    }
+
+    #[test]
+    fn coverage_ratio_reflects_occupied_leaf_area() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let empty = create_quad_tree(boundary);
+        assert_eq!(coverage_ratio(&empty), 0.0);
+
+        let mut populated = create_quad_tree(boundary);
+        insert(&mut populated, Point(1.0, 1.0));
+        assert_eq!(coverage_ratio(&populated), 1.0);
+    }
+
+    #[test]
+    fn identified_quad_tree_looks_up_points_by_id() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_identified_quad_tree(boundary);
+
+        assert!(insert_with_id(&mut tree, 42, Point(1.0, 1.0)));
+        assert_eq!(point_by_id(&tree, 42), Some(Point(1.0, 1.0)));
+        assert_eq!(point_by_id(&tree, 7), None);
+    }
+
+    #[test]
+    fn retain_drops_points_failing_the_predicate() {
+        let mut tree = sample_tree();
+
+        retain(&mut tree, &mut |p| p.0 < 50.0);
+
+        assert!(all_points(&tree).iter().all(|p| p.0 < 50.0));
+    }
+
+    #[test]
+    fn boundary_can_be_used_as_a_hash_map_key() {
+        let mut map = std::collections::HashMap::new();
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        map.insert(boundary, "root");
+
+        assert_eq!(map.get(&Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 }), Some(&"root"));
+    }
+
+    #[test]
+    fn find_duplicates_reports_repeated_coordinates() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        insert(&mut tree, Point(4.0, 4.0));
+        insert(&mut tree, Point(4.0, 4.0));
+        insert(&mut tree, Point(1.0, 1.0));
+
+        assert_eq!(find_duplicates(&tree), vec![(Point(4.0, 4.0), 2)]);
+    }
+
+    #[test]
+    fn downsample_to_n_never_exceeds_the_requested_count() {
+        let tree = sample_tree();
+
+        assert!(downsample_to_n(&tree, 10).len() <= 10);
+        assert_eq!(downsample_to_n(&tree, 10_000).len(), count_points(&tree));
+    }
+
+    #[test]
+    fn group_by_grid_with_zero_cols_or_rows_returns_no_groups() {
+        let tree = sample_tree();
+        assert!(group_by_grid(&tree, 0, 4).is_empty());
+        assert!(group_by_grid(&tree, 4, 0).is_empty());
+    }
+
+    #[test]
+    fn occupancy_bitmap_with_zero_cols_or_rows_returns_empty() {
+        let tree = sample_tree();
+        assert!(occupancy_bitmap(&tree, 0, 4).is_empty());
+        assert!(occupancy_bitmap(&tree, 4, 0).is_empty());
+    }
+
+    #[test]
+    fn downsample_to_n_of_zero_returns_no_points() {
+        let tree = sample_tree();
+        assert!(downsample_to_n(&tree, 0).is_empty());
+    }
+
+    #[test]
+    fn downsample_to_n_covers_multiple_quadrants() {
+        let tree = sample_tree();
+        let sampled = downsample_to_n(&tree, 40);
+
+        let midpoint = Point(50.0, 50.0);
+        let quadrants_seen: std::collections::HashSet<(bool, bool)> = sampled
+            .iter()
+            .map(|&Point(x, y)| (x < midpoint.0, y < midpoint.1))
+            .collect();
+        assert!(
+            quadrants_seen.len() > 1,
+            "expected the sample to span more than one quadrant, saw {sampled:?}"
+        );
+    }
+
+    #[test]
+    fn quadrant_boundaries_partition_the_input_boundary() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 20.0 };
+
+        let quadrants = quadrant_boundaries(&boundary);
+
+        assert_eq!(quadrants[0], Boundary { x1: 0.0, x2: 5.0, y1: 0.0, y2: 10.0 });
+        assert_eq!(quadrants[3], Boundary { x1: 5.0, x2: 10.0, y1: 10.0, y2: 20.0 });
+    }
+
+    #[test]
+    fn point_mass_at_depth_preserves_the_total_point_count() {
+        let tree = sample_tree();
+
+        let total: usize = point_mass_at_depth(&tree, 1).iter().map(|&(_, count)| count).sum();
+
+        assert_eq!(total, count_points(&tree));
+    }
+
+    #[test]
+    fn tree_contains_boundary_checks_enclosure() {
+        let tree = sample_tree();
+        let inside = Boundary { x1: 10.0, x2: 20.0, y1: 10.0, y2: 20.0 };
+        let outside = Boundary { x1: -10.0, x2: 20.0, y1: 10.0, y2: 20.0 };
+
+        assert!(tree_contains_boundary(&tree, &inside));
+        assert!(!tree_contains_boundary(&tree, &outside));
+    }
+
+    #[test]
+    fn search_clipped_keeps_results_on_the_query_boundary() {
+        let tree = sample_tree();
+        let boundary = Boundary { x1: 10.0, x2: 20.0, y1: 10.0, y2: 20.0 };
+
+        for point in search_clipped(&tree, &boundary, 5.0) {
+            assert!(contains(&boundary, point));
+        }
+    }
+
+    #[test]
+    fn compact_search_matches_search_on_the_same_points() {
+        let boundary = Boundary { x1: 0.0, x2: 100.0, y1: 0.0, y2: 100.0 };
+        let mut tree = create_quad_tree(boundary);
+        let mut compact = create_compact_quad_tree(boundary);
+        for i in 0..14 {
+            for j in 0..14 {
+                let point = Point(i as f64 * 7.0 + 1.0, j as f64 * 5.0 + 2.0);
+                assert!(insert(&mut tree, point));
+                assert!(compact_insert(&mut compact, point));
+            }
+        }
+
+        let window = Boundary { x1: 10.0, x2: 60.0, y1: 10.0, y2: 60.0 };
+        let mut expected = search(&tree, &window);
+        let mut actual: Vec<Point> = compact_search(&compact, &window)
+            .into_iter()
+            .map(|index| compact.points[index as usize])
+            .collect();
+
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        actual.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn compact_insert_rejects_points_outside_the_boundary() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut compact = create_compact_quad_tree(boundary);
+
+        assert!(!compact_insert(&mut compact, Point(20.0, 20.0)));
+        assert!(compact.points.is_empty());
+    }
+
+    #[test]
+    fn insert_with_path_never_leaves_points_on_a_subdivided_node() {
+        let boundary = Boundary { x1: 0.0, x2: 100.0, y1: 0.0, y2: 100.0 };
+        let mut tree = create_quad_tree(boundary);
+        for i in 0..(MAX_CAPACITY + 10) {
+            insert(&mut tree, Point(i as f64 % 100.0, (i as f64 * 3.0) % 100.0));
+        }
+        assert!(tree.children.is_some());
+
+        assert!(insert_with_path(&mut tree, Point(5.0, 5.0), &[]));
+        assert!(validate(&tree).is_ok());
+    }
+
+    #[test]
+    fn insert_with_path_follows_the_given_quadrants() {
+        let boundary = Boundary { x1: 0.0, x2: 100.0, y1: 0.0, y2: 100.0 };
+        let mut tree = create_quad_tree(boundary);
+        let point = Point(10.0, 10.0);
+
+        assert!(insert_with_path(&mut tree, point, &[Quadrant::TopLeft]));
+        assert!(validate(&tree).is_ok());
+        assert!(all_points(&tree).contains(&point));
+        assert!(points(top_left(&tree).unwrap()).contains(&point));
+    }
+
+    #[test]
+    fn compact_insert_subdivides_past_max_capacity() {
+        let boundary = Boundary { x1: 0.0, x2: 1000.0, y1: 0.0, y2: 1000.0 };
+        let mut compact = create_compact_quad_tree(boundary);
+
+        for i in 0..(MAX_CAPACITY + 50) {
+            assert!(compact_insert(&mut compact, Point(i as f64, i as f64)));
+        }
+
+        assert!(compact.root.children.is_some());
+        assert!(compact.root.point_indices.is_empty());
+        assert_eq!(
+            compact_search(&compact, &boundary).len(),
+            MAX_CAPACITY + 50
+        );
+    }
+
+    #[test]
+    fn insert_reservoir_evicts_oldest_once_full_at_max_depth() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+
+        for i in 0..5 {
+            assert!(insert_reservoir(&mut tree, Point(1.0, i as f64), 3, 0));
+        }
+
+        assert_eq!(count_points(&tree), 3);
+        assert!(!all_points(&tree).contains(&Point(1.0, 0.0)));
+        assert!(all_points(&tree).contains(&Point(1.0, 4.0)));
+    }
+
+    #[test]
+    fn insert_reservoir_subdivides_like_insert_below_max_depth() {
+        let boundary = Boundary { x1: 0.0, x2: 100.0, y1: 0.0, y2: 100.0 };
+        let mut tree = create_quad_tree(boundary);
+
+        for i in 0..(MAX_CAPACITY + 10) {
+            insert_reservoir(&mut tree, Point(i as f64 % 100.0, (i as f64 * 3.0) % 100.0), MAX_CAPACITY, 8);
+        }
+
+        assert!(tree.children.is_some());
+        assert!(validate(&tree).is_ok());
+    }
+
+    #[test]
+    fn insert_with_tie_break_uses_the_first_matching_priority_quadrant() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        for _ in 0..MAX_CAPACITY {
+            insert(&mut tree, Point(1.0, 1.0));
+        }
+
+        let midline_point = Point(5.0, 1.0);
+        assert!(insert_with_tie_break(&mut tree, midline_point, &QUADRANTS));
+        assert!(validate(&tree).is_ok());
+        assert!(all_points(&tree).contains(&midline_point));
+        assert!(all_points(top_left(&tree).unwrap()).contains(&midline_point));
+    }
+
+    #[test]
+    fn grid_insert_and_search_span_every_cell() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut grid = create_grid_quad_tree(boundary, 2, 2);
+
+        assert!(grid_insert(&mut grid, Point(1.0, 1.0)));
+        assert!(grid_insert(&mut grid, Point(9.0, 9.0)));
+        assert!(!grid_insert(&mut grid, Point(20.0, 20.0)));
+
+        let mut found = grid_search(&grid, &boundary);
+        found.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(found, vec![Point(1.0, 1.0), Point(9.0, 9.0)]);
+    }
+
+    #[test]
+    fn insert_ordered_tracks_insertion_order() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut ordered = create_ordered_quad_tree(boundary);
+
+        assert!(insert_ordered(&mut ordered, Point(3.0, 3.0)));
+        assert!(insert_ordered(&mut ordered, Point(1.0, 1.0)));
+        assert!(!insert_ordered(&mut ordered, Point(20.0, 20.0)));
+
+        assert_eq!(ordered.insertion_order, vec![Point(3.0, 3.0), Point(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn insert_watched_fires_callbacks_for_contained_points() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        let mut watches = WatchList::default();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        let seen_clone = Rc::clone(&seen);
+        watch(&mut watches, Boundary { x1: 0.0, x2: 5.0, y1: 0.0, y2: 5.0 }, move |p| {
+            seen_clone.borrow_mut().push(p);
+        });
+
+        assert!(insert_watched(&mut tree, Point(1.0, 1.0), &mut watches));
+        assert!(insert_watched(&mut tree, Point(9.0, 9.0), &mut watches));
+
+        assert_eq!(*seen.borrow(), vec![Point(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn to_dot_renders_a_node_per_tree_node() {
+        let tree = sample_tree();
+        let dot = to_dot(&tree);
+
+        assert!(dot.starts_with("digraph Quadtree {\n"));
+        assert!(dot.trim_end().ends_with('}'));
+        assert!(dot.contains("points="));
+    }
+
+    #[test]
+    fn to_json_includes_the_root_boundary_and_points() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        insert(&mut tree, Point(1.0, 2.0));
+
+        let json = to_json(&tree);
+        assert!(json.contains("\"x1\":0"));
+        assert!(json.contains("[1,2]"));
+    }
+
+    #[test]
+    fn haversine_distance_between_identical_points_is_zero() {
+        let point = Point(51.5, -0.1);
+        assert_eq!(haversine_distance(point, point), 0.0);
+        assert!(haversine_distance(Point(0.0, 0.0), Point(0.0, 90.0)) > 0.0);
+    }
+
+    #[test]
+    fn nearest_neighbor_haversine_finds_the_closest_point() {
+        let boundary = Boundary { x1: -90.0, x2: 90.0, y1: -180.0, y2: 180.0 };
+        let mut tree = create_quad_tree(boundary);
+        insert(&mut tree, Point(10.0, 10.0));
+        insert(&mut tree, Point(-10.0, -10.0));
+
+        assert_eq!(nearest_neighbor_haversine(&tree, Point(9.0, 9.0)), Some(Point(10.0, 10.0)));
+    }
+
+    #[test]
+    fn search_oriented_rect_finds_points_inside_the_rotated_rectangle() {
+        let tree = sample_tree();
+        let inside = search_oriented_rect(&tree, Point(50.0, 50.0), 20.0, 20.0, std::f64::consts::FRAC_PI_4);
+
+        for point in &inside {
+            assert!(distance(Point(50.0, 50.0), *point) <= 20.0);
+        }
+        assert!(!inside.is_empty());
+    }
+
+    #[test]
+    fn convex_hull_in_region_wraps_every_point_in_the_region() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        for point in [Point(0.0, 0.0), Point(10.0, 0.0), Point(10.0, 10.0), Point(0.0, 10.0), Point(5.0, 5.0)] {
+            insert(&mut tree, point);
+        }
+
+        let hull = convex_hull_in_region(&tree, &boundary);
+        assert!(hull.len() >= 3);
+        assert!(!hull.contains(&Point(5.0, 5.0)));
+    }
+
+    #[test]
+    fn search_with_budget_reports_exhaustion_on_a_tight_budget() {
+        let tree = sample_tree();
+
+        let (_, exhausted_immediately) = search_with_budget(&tree, &tree.boundary, 0);
+        assert!(exhausted_immediately);
+
+        let (results, not_exhausted) = search_with_budget(&tree, &tree.boundary, 10_000);
+        assert!(!not_exhausted);
+        assert_eq!(results.len(), count_points(&tree));
+    }
+
+    #[test]
+    fn rotate_dataset_preserves_point_count_for_a_full_turn() {
+        let mut tree = sample_tree();
+        let before = count_points(&tree);
+
+        rotate_dataset(&mut tree, Point(50.0, 50.0), std::f64::consts::TAU);
+
+        assert_eq!(count_points(&tree), before);
+    }
+
+    #[test]
+    fn similarity_of_a_tree_with_itself_is_one() {
+        let tree = sample_tree();
+        assert_eq!(similarity(&tree, &tree), 1.0);
+    }
+
+    #[test]
+    fn similarity_of_disjoint_trees_is_zero() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut a = create_quad_tree(boundary);
+        let mut b = create_quad_tree(boundary);
+        insert(&mut a, Point(1.0, 1.0));
+        insert(&mut b, Point(9.0, 9.0));
+
+        assert_eq!(similarity(&a, &b), 0.0);
+    }
+
+    #[test]
+    fn spatial_partitions_covers_every_point_across_k_groups() {
+        let tree = sample_tree();
+        let total = count_points(&tree);
+
+        let partitions = spatial_partitions(&tree, 4);
+        assert!(partitions.len() <= 4);
+        assert_eq!(partitions.iter().map(Vec::len).sum::<usize>(), total);
+    }
+
+    #[test]
+    fn search_adaptive_expands_until_it_finds_a_point() {
+        let boundary = Boundary { x1: 0.0, x2: 100.0, y1: 0.0, y2: 100.0 };
+        let mut tree = create_quad_tree(boundary);
+        insert(&mut tree, Point(90.0, 90.0));
+
+        let tiny_window = Boundary { x1: 49.0, x2: 51.0, y1: 49.0, y2: 51.0 };
+        let found = search_adaptive(&tree, &tiny_window, 2.0, 10);
+
+        assert_eq!(found, vec![Point(90.0, 90.0)]);
+    }
+
+    #[test]
+    fn search_paginated_walks_every_page_without_overlap() {
+        let tree = sample_tree();
+        let total = count_points(&tree);
+
+        let mut seen = Vec::new();
+        let mut token = None;
+        loop {
+            let (page, next) = search_paginated(&tree, &tree.boundary, 20, token);
+            seen.extend(page);
+            match next {
+                Some(t) => token = Some(t),
+                None => break,
+            }
+        }
+
+        assert_eq!(seen.len(), total);
+    }
+
+    #[test]
+    fn nearest_neighbor_batch_preserves_query_order() {
+        let boundary = Boundary { x1: 0.0, x2: 10.0, y1: 0.0, y2: 10.0 };
+        let mut tree = create_quad_tree(boundary);
+        insert(&mut tree, Point(1.0, 1.0));
+        insert(&mut tree, Point(9.0, 9.0));
+
+        let results = nearest_neighbor_batch(&tree, &[Point(0.0, 0.0), Point(10.0, 10.0)]);
+        assert_eq!(results, vec![Some(Point(1.0, 1.0)), Some(Point(9.0, 9.0))]);
+    }
 }